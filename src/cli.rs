@@ -0,0 +1,144 @@
+use crate::models::ImportPolicy;
+use crate::services::ArchiveService;
+use crate::state::AppState;
+
+pub const USAGE: &str = "\
+Usage:
+  pass-cookie-report-rust                       Run the HTTP server
+  pass-cookie-report-rust cache export [--store ID] [--from DD/MM/YYYY --to DD/MM/YYYY] [--output FILE]
+  pass-cookie-report-rust cache import FILE [--store ID] [--policy merge|overwrite|skip-existing]
+
+Cache commands use the same config (env / CONFIG_FILE) as the server. Without --store,
+export uses STORE_ID and import uses the store recorded in the archive. Both exit with
+status 1 if a cache file cannot be loaded; export never modifies the cache files. Stop the
+server before importing into its cache file, otherwise its next save overwrites the import.
+";
+
+/// Subcommand dari argumen command line; tanpa argumen server HTTP dijalankan seperti biasa
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Serve,
+    Help,
+    CacheExport {
+        store: Option<String>,
+        from: Option<String>,
+        to: Option<String>,
+        output: Option<String>,
+    },
+    CacheImport {
+        input: String,
+        store: Option<String>,
+        policy: ImportPolicy,
+    },
+}
+
+impl Command {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut args = args.iter().map(String::as_str);
+        match (args.next(), args.next()) {
+            (None, _) => Ok(Command::Serve),
+            (Some("help" | "--help" | "-h"), _) => Ok(Command::Help),
+            (Some("cache"), Some("export")) => {
+                let (mut store, mut from, mut to, mut output) = (None, None, None, None);
+                while let Some(flag) = args.next() {
+                    let target = match flag {
+                        "--store" => &mut store,
+                        "--from" => &mut from,
+                        "--to" => &mut to,
+                        "--output" | "-o" => &mut output,
+                        other => return Err(format!("Unknown option '{}' for cache export", other)),
+                    };
+                    *target = Some(args.next().ok_or_else(|| format!("{} needs a value", flag))?.to_string());
+                }
+                if from.is_some() != to.is_some() {
+                    return Err("Both --from and --to are required for a range".to_string());
+                }
+                Ok(Command::CacheExport { store, from, to, output })
+            }
+            (Some("cache"), Some("import")) => {
+                let (mut input, mut store, mut policy) = (None, None, ImportPolicy::default());
+                while let Some(arg) = args.next() {
+                    match arg {
+                        "--store" => store = Some(args.next().ok_or("--store needs a value")?.to_string()),
+                        "--policy" => {
+                            let value = args.next().ok_or("--policy needs a value")?;
+                            policy = ImportPolicy::parse(value)
+                                .ok_or_else(|| format!("Unknown import policy '{}'", value))?;
+                        }
+                        other if other.starts_with('-') => {
+                            return Err(format!("Unknown option '{}' for cache import", other));
+                        }
+                        file if input.is_none() => input = Some(file.to_string()),
+                        extra => return Err(format!("Unexpected argument '{}'", extra)),
+                    }
+                }
+                let input = input.ok_or("cache import needs an archive FILE")?;
+                Ok(Command::CacheImport { input, store, policy })
+            }
+            (Some(command), sub) => Err(format!(
+                "Unknown command '{}{}'",
+                command,
+                sub.map(|s| format!(" {}", s)).unwrap_or_default()
+            )),
+        }
+    }
+}
+
+/// Jalankan subcommand cache pada state yang sudah me-load file cache (load gagal = tidak sampai sini);
+/// mengembalikan exit code
+pub async fn run(command: Command, state: &AppState) -> i32 {
+    match command {
+        Command::CacheExport { store, from, to, output } => {
+            let exported = match state.config().resolve_store(store.as_deref()) {
+                Ok(store) => ArchiveService::export(state, &store, from.as_deref(), to.as_deref()).await,
+                Err(e) => Err(e),
+            };
+            let (manifest, bytes) = match exported {
+                Ok(result) => result,
+                Err(e) => {
+                    eprintln!("Export failed: {}", e.message());
+                    return 1;
+                }
+            };
+            let path = output.unwrap_or_else(|| ArchiveService::filename(&manifest));
+            if let Err(e) = tokio::fs::write(&path, &bytes).await {
+                eprintln!("Cannot write {}: {}", path, e);
+                return 1;
+            }
+            println!(
+                "Exported {} dates ({} transactions) for store {} to {}",
+                manifest.total_dates, manifest.total_transactions, manifest.store_id, path
+            );
+            0
+        }
+        Command::CacheImport { input, store, policy } => {
+            let archive = match tokio::fs::read(&input).await {
+                Ok(archive) => archive,
+                Err(e) => {
+                    eprintln!("Cannot read {}: {}", input, e);
+                    return 1;
+                }
+            };
+            match ArchiveService::import(state, archive, policy, store.as_deref()).await {
+                Ok((manifest, summary)) => {
+                    println!(
+                        "Imported {} ({} dates from {}): {} added, {} merged, {} overwritten, {} skipped",
+                        input,
+                        manifest.total_dates,
+                        manifest.created_at.format("%Y-%m-%d %H:%M UTC"),
+                        summary.added,
+                        summary.merged,
+                        summary.overwritten,
+                        summary.skipped
+                    );
+                    0
+                }
+                Err(e) => {
+                    eprintln!("Import failed: {}", e.message());
+                    1
+                }
+            }
+        }
+        Command::Serve | Command::Help => 0,
+    }
+}
@@ -0,0 +1,718 @@
+use crate::errors::DebugAppError;
+use crate::models::{CsvColumn, CsvOptions};
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::{env, fs};
+use tracing_subscriber::EnvFilter;
+
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+/// Nama akun untuk kredensial `upstream.*` di level atas (store yang tidak ada di `upstream.accounts`)
+pub const DEFAULT_ACCOUNT: &str = "default";
+
+/// Konfigurasi aplikasi, di-load saat startup lalu disimpan di `AppState`.
+/// Urutan prioritas: default < file TOML (`CONFIG_FILE`, default `config.toml` kalau ada) < env.
+/// Bisa di-reload saat runtime (SIGHUP / file berubah), lihat `Config::reload`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub upstream: UpstreamConfig,
+    pub cache: CacheConfig,
+    pub export: ExportConfig,
+    pub telemetry: TelemetryConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    pub log_level: String,
+    /// `text` (default) atau `json` (satu objek per baris, field terstruktur + span request)
+    pub log_format: LogFormat,
+    /// Batas waktu drain request dan job saat SIGTERM/SIGINT sebelum save cache terakhir
+    pub shutdown_timeout_secs: u64,
+    /// Bearer token untuk endpoint `/admin/*`; kalau kosong endpoint admin dimatikan
+    pub admin_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, ()> {
+        match value.to_ascii_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogFormat::Text => write!(f, "text"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpstreamConfig {
+    /// Wajib, tanpa default (dulu diam-diam fallback ke `https://example.com`)
+    pub api_base_url: String,
+    /// Wajib, tanpa default (dulu diam-diam fallback ke store `1`). Store default untuk request
+    /// tanpa `store_id`; file cache dan folder spill-nya tetap `cache.file_path` / `cache.spill_dir`.
+    pub store_id: String,
+    /// Store lain yang boleh diminta lewat `?store_id=` (selain `store_id`)
+    pub store_ids: Vec<String>,
+    pub api_timestamp: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Cookie sesi yang sudah ada, dipakai sebelum login dengan `username`/`password`
+    pub cookie: Option<String>,
+    /// Akun POS lain dengan login sendiri (`[[upstream.accounts]]`), masing-masing untuk store-nya
+    pub accounts: Vec<UpstreamAccount>,
+    /// Jeda acak antar request halaman upstream
+    pub page_delay_min_ms: u64,
+    pub page_delay_max_ms: u64,
+}
+
+/// Satu login upstream. Store-nya ikut bisa diminta lewat `?store_id=`; fetch untuk store itu
+/// memakai `api_base_url` dan sesi akun ini. Password/cookie bisa dari env
+/// `UPSTREAM_ACCOUNT_<NAME>_PASSWORD` / `UPSTREAM_ACCOUNT_<NAME>_COOKIE`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpstreamAccount {
+    pub name: String,
+    /// Kosong = `upstream.api_base_url`
+    #[serde(default)]
+    pub api_base_url: Option<String>,
+    pub store_ids: Vec<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub cookie: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    pub file_path: String,
+    pub audit_log_file_path: String,
+    /// Entri audit terbaru yang disimpan di memory untuk `/audit/changes`; file tetap menyimpan semuanya
+    pub audit_log_max_entries: usize,
+    /// Fetch upstream (smart fetch, force refresh) yang boleh berjalan bersamaan; selebihnya `503`
+    pub max_concurrent_jobs: usize,
+    /// Fetch upstream yang lebih lama dari ini dibatalkan dengan `504`
+    pub job_timeout_secs: u64,
+    /// `true`: cache file yang tidak bisa di-load/migrasi menghentikan startup.
+    /// `false`: file dipindah ke samping (`.unreadable-<waktu>`) dan server start dengan cache kosong.
+    pub strict_load: bool,
+    /// Batas memory untuk tanggal di memory cache (ukuran terukur, bukan estimasi); 0 = tanpa batas.
+    /// Tanggal yang paling lama tidak diakses dipindah ke `spill_dir` dan di-load lagi saat diminta.
+    pub memory_budget_mb: usize,
+    /// Folder file per tanggal untuk tanggal yang dikeluarkan dari memory
+    pub spill_dir: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExportConfig {
+    pub csv_columns: Option<String>,
+    pub csv_delimiter: Option<String>,
+    pub csv_date_format: Option<String>,
+    pub parquet_export_dir: String,
+    pub pdf_template_path: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TelemetryConfig {
+    /// Collector OTLP/HTTP, mis. `http://localhost:4318`; kosong = trace tidak diekspor
+    pub otlp_endpoint: Option<String>,
+    pub service_name: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "0.0.0.0".to_string(),
+            port: 3000,
+            log_level: "info".to_string(),
+            log_format: LogFormat::Text,
+            shutdown_timeout_secs: 30,
+            admin_token: None,
+        }
+    }
+}
+
+impl Default for UpstreamConfig {
+    fn default() -> Self {
+        Self {
+            api_base_url: String::new(),
+            store_id: String::new(),
+            store_ids: Vec::new(),
+            api_timestamp: "1234567890".to_string(),
+            username: None,
+            password: None,
+            cookie: None,
+            accounts: Vec::new(),
+            page_delay_min_ms: 250,
+            page_delay_max_ms: 750,
+        }
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            file_path: "cache_backup.json".to_string(),
+            audit_log_file_path: "audit_log.jsonl".to_string(),
+            audit_log_max_entries: 10_000,
+            max_concurrent_jobs: 3,
+            job_timeout_secs: 300,
+            strict_load: false,
+            memory_budget_mb: 256,
+            spill_dir: "cache_spill".to_string(),
+        }
+    }
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            csv_columns: None,
+            csv_delimiter: None,
+            csv_date_format: None,
+            parquet_export_dir: "parquet_export".to_string(),
+            pdf_template_path: None,
+        }
+    }
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            service_name: "pass-cookie-report-rust".to_string(),
+        }
+    }
+}
+
+/// Semua masalah konfigurasi sekaligus, supaya operator tidak perlu restart berkali-kali
+#[derive(Debug)]
+pub struct ConfigError(pub Vec<String>);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Invalid configuration:")?;
+        for problem in &self.0 {
+            writeln!(f, "  - {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+/// Hasil reload: config baru yang siap dipasang plus ringkasan untuk log
+pub struct ConfigReload {
+    pub config: Config,
+    /// Baris `key: lama -> baru` (password tetap disamarkan)
+    pub changed: Vec<String>,
+    /// Key struktural yang berubah di file tapi diabaikan sampai restart
+    pub ignored: Vec<String>,
+}
+
+impl Config {
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut problems = Vec::new();
+
+        let mut config = match Self::config_file_path() {
+            Some(path) => match Self::from_file(&path) {
+                Ok(config) => config,
+                Err(e) => {
+                    problems.push(e);
+                    Config::default()
+                }
+            },
+            None => Config::default(),
+        };
+
+        config.apply_env(&mut problems);
+        config.validate(&mut problems);
+
+        if problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError(problems))
+        }
+    }
+
+    /// Load ulang lalu pertahankan key struktural dari config yang sedang jalan: alamat bind,
+    /// format log, telemetry, lokasi file, dan upstream/store yang menentukan isi cache hanya berlaku setelah restart
+    pub fn reload(current: &Config) -> Result<ConfigReload, ConfigError> {
+        let mut config = Self::load()?;
+
+        let mut ignored = Vec::new();
+        let structural = [
+            ("server.host", &mut config.server.host, &current.server.host),
+            ("upstream.api_base_url", &mut config.upstream.api_base_url, &current.upstream.api_base_url),
+            ("upstream.store_id", &mut config.upstream.store_id, &current.upstream.store_id),
+            ("cache.file_path", &mut config.cache.file_path, &current.cache.file_path),
+            ("cache.audit_log_file_path", &mut config.cache.audit_log_file_path, &current.cache.audit_log_file_path),
+            ("cache.spill_dir", &mut config.cache.spill_dir, &current.cache.spill_dir),
+        ];
+        for (key, new, old) in structural {
+            if new != old {
+                ignored.push(key.to_string());
+                *new = old.clone();
+            }
+        }
+        if config.upstream.store_ids != current.upstream.store_ids {
+            ignored.push("upstream.store_ids".to_string());
+            config.upstream.store_ids = current.upstream.store_ids.clone();
+        }
+        // Nama, URL dan store akun menentukan isi cache; kredensialnya boleh diganti saat jalan
+        let layout = |accounts: &[UpstreamAccount]| -> Vec<(String, Option<String>, Vec<String>)> {
+            accounts.iter().map(|a| (a.name.clone(), a.api_base_url.clone(), a.store_ids.clone())).collect()
+        };
+        if layout(&config.upstream.accounts) != layout(&current.upstream.accounts) {
+            ignored.push("upstream.accounts".to_string());
+            config.upstream.accounts = current.upstream.accounts.clone();
+        }
+        if config.server.port != current.server.port {
+            ignored.push("server.port".to_string());
+            config.server.port = current.server.port;
+        }
+        if config.server.log_format != current.server.log_format {
+            ignored.push("server.log_format".to_string());
+            config.server.log_format = current.server.log_format;
+        }
+        if config.telemetry != current.telemetry {
+            ignored.push("telemetry".to_string());
+            config.telemetry = current.telemetry.clone();
+        }
+
+        let old_lines = current.to_string();
+        let mut changed: Vec<String> = config
+            .to_string()
+            .lines()
+            .zip(old_lines.lines())
+            .filter(|(new, old)| new != old)
+            .map(|(new, old)| {
+                let (key, new_value) = new.split_once(" = ").unwrap_or((new, ""));
+                let old_value = old.split_once(" = ").map(|(_, v)| v).unwrap_or("");
+                format!("{}: {} -> {}", key, old_value, new_value)
+            })
+            .collect();
+        // Secret yang diganti tetap tampil `***` di kedua sisi, jadi dicatat terpisah
+        let mut secrets = vec![
+            ("upstream.password".to_string(), &config.upstream.password, &current.upstream.password),
+            ("upstream.cookie".to_string(), &config.upstream.cookie, &current.upstream.cookie),
+            ("server.admin_token".to_string(), &config.server.admin_token, &current.server.admin_token),
+        ];
+        for (new, old) in config.upstream.accounts.iter().zip(&current.upstream.accounts) {
+            secrets.push((format!("upstream.accounts.{}.password", new.name), &new.password, &old.password));
+            secrets.push((format!("upstream.accounts.{}.cookie", new.name), &new.cookie, &old.cookie));
+        }
+        for (key, new, old) in secrets {
+            if new.is_some() && old.is_some() && new != old {
+                changed.push(format!("{}: *** -> *** (rotated)", key));
+            }
+        }
+
+        Ok(ConfigReload { config, changed, ignored })
+    }
+
+    /// `CONFIG_FILE` harus ada kalau di-set; `config.toml` default opsional
+    pub fn config_file_path() -> Option<String> {
+        match env::var("CONFIG_FILE") {
+            Ok(path) if !path.is_empty() => Some(path),
+            _ => Path::new(DEFAULT_CONFIG_FILE).exists().then(|| DEFAULT_CONFIG_FILE.to_string()),
+        }
+    }
+
+    fn from_file(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read config file '{}': {}", path, e))?;
+        toml::from_str(&content).map_err(|e| format!("Invalid config file '{}': {}", path, e))
+    }
+
+    fn apply_env(&mut self, problems: &mut Vec<String>) {
+        env_value(problems, "HOST", &mut self.server.host);
+        env_value(problems, "PORT", &mut self.server.port);
+        env_value(problems, "LOG_LEVEL", &mut self.server.log_level);
+        env_value(problems, "LOG_FORMAT", &mut self.server.log_format);
+        env_value(problems, "SHUTDOWN_TIMEOUT_SECS", &mut self.server.shutdown_timeout_secs);
+        env_option("ADMIN_TOKEN", &mut self.server.admin_token);
+
+        env_value(problems, "API_BASE_URL", &mut self.upstream.api_base_url);
+        env_value(problems, "STORE_ID", &mut self.upstream.store_id);
+        env_list("STORE_IDS", &mut self.upstream.store_ids);
+        env_value(problems, "API_TIMESTAMP", &mut self.upstream.api_timestamp);
+        env_option("UPSTREAM_USERNAME", &mut self.upstream.username);
+        env_option("UPSTREAM_PASSWORD", &mut self.upstream.password);
+        env_option("UPSTREAM_COOKIE", &mut self.upstream.cookie);
+        for account in &mut self.upstream.accounts {
+            let prefix = format!("UPSTREAM_ACCOUNT_{}", account.name.to_ascii_uppercase().replace('-', "_"));
+            env_option(&format!("{}_PASSWORD", prefix), &mut account.password);
+            env_option(&format!("{}_COOKIE", prefix), &mut account.cookie);
+        }
+        env_value(problems, "PAGE_DELAY_MIN_MS", &mut self.upstream.page_delay_min_ms);
+        env_value(problems, "PAGE_DELAY_MAX_MS", &mut self.upstream.page_delay_max_ms);
+
+        env_value(problems, "CACHE_FILE_PATH", &mut self.cache.file_path);
+        env_value(problems, "AUDIT_LOG_FILE_PATH", &mut self.cache.audit_log_file_path);
+        env_value(problems, "AUDIT_LOG_MAX_ENTRIES", &mut self.cache.audit_log_max_entries);
+        env_value(problems, "MAX_CONCURRENT_JOBS", &mut self.cache.max_concurrent_jobs);
+        env_value(problems, "JOB_TIMEOUT_SECS", &mut self.cache.job_timeout_secs);
+        env_value(problems, "CACHE_STRICT_LOAD", &mut self.cache.strict_load);
+        env_value(problems, "CACHE_MEMORY_BUDGET_MB", &mut self.cache.memory_budget_mb);
+        env_value(problems, "CACHE_SPILL_DIR", &mut self.cache.spill_dir);
+
+        env_option("CSV_COLUMNS", &mut self.export.csv_columns);
+        env_option("CSV_DELIMITER", &mut self.export.csv_delimiter);
+        env_option("CSV_DATE_FORMAT", &mut self.export.csv_date_format);
+        env_value(problems, "PARQUET_EXPORT_DIR", &mut self.export.parquet_export_dir);
+        env_option("PDF_TEMPLATE_PATH", &mut self.export.pdf_template_path);
+
+        env_option("OTEL_EXPORTER_OTLP_ENDPOINT", &mut self.telemetry.otlp_endpoint);
+        env_value(problems, "OTEL_SERVICE_NAME", &mut self.telemetry.service_name);
+    }
+
+    fn validate(&mut self, problems: &mut Vec<String>) {
+        let base_url = self.upstream.api_base_url.trim().trim_end_matches('/').to_string();
+        if base_url.is_empty() {
+            problems.push("API_BASE_URL is required".to_string());
+        } else if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
+            problems.push(format!("API_BASE_URL must start with http:// or https:// (got '{}')", base_url));
+        }
+        self.upstream.api_base_url = base_url;
+
+        // Id store masuk ke cache file, spill, job id dan audit log; store tambahan juga jadi bagian
+        // nama file cache dan folder spill
+        if self.upstream.store_id.trim().is_empty() {
+            problems.push("STORE_ID is required".to_string());
+        } else if !is_identifier(&self.upstream.store_id) {
+            problems.push(format!("STORE_ID may only contain letters, digits, '-' and '_' (got '{}')", self.upstream.store_id));
+        }
+        for store_id in &self.upstream.store_ids {
+            if !is_identifier(store_id) {
+                problems.push(format!("STORE_IDS may only contain letters, digits, '-' and '_' (got '{}')", store_id));
+            }
+        }
+        self.validate_accounts(problems);
+        if self.cache.spill_dir.trim().is_empty() {
+            problems.push("CACHE_SPILL_DIR must not be empty".to_string());
+        }
+        if self.upstream.username.is_some() && self.upstream.password.is_none() {
+            problems.push("UPSTREAM_PASSWORD is required when UPSTREAM_USERNAME is set".to_string());
+        }
+
+        for (name, value) in [
+            ("MAX_CONCURRENT_JOBS", self.cache.max_concurrent_jobs),
+            ("AUDIT_LOG_MAX_ENTRIES", self.cache.audit_log_max_entries),
+            ("JOB_TIMEOUT_SECS", self.cache.job_timeout_secs as usize),
+        ] {
+            if value == 0 {
+                problems.push(format!("{} must be greater than 0", name));
+            }
+        }
+        if let Some(endpoint) = &self.telemetry.otlp_endpoint {
+            if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+                problems.push(format!("OTEL_EXPORTER_OTLP_ENDPOINT must start with http:// or https:// (got '{}')", endpoint));
+            }
+        }
+
+        if EnvFilter::try_new(&self.server.log_level).is_err() {
+            problems.push(format!("LOG_LEVEL is not a valid filter: '{}'", self.server.log_level));
+        }
+        if self.upstream.page_delay_min_ms > self.upstream.page_delay_max_ms {
+            problems.push("PAGE_DELAY_MIN_MS must not be greater than PAGE_DELAY_MAX_MS".to_string());
+        }
+
+        if let Some(delimiter) = &self.export.csv_delimiter {
+            if CsvOptions::parse_delimiter(delimiter).is_none() {
+                problems.push(format!(
+                    "CSV_DELIMITER must be a single ASCII character or tab/semicolon/comma (got '{}')",
+                    delimiter
+                ));
+            }
+        }
+        if let Some(columns) = &self.export.csv_columns {
+            for name in columns.split(',').filter(|name| !name.trim().is_empty()) {
+                if CsvColumn::parse(name).is_none() {
+                    problems.push(format!("Unknown CSV column in CSV_COLUMNS: {}", name));
+                }
+            }
+        }
+    }
+
+    fn validate_accounts(&mut self, problems: &mut Vec<String>) {
+        let mut names: Vec<&str> = Vec::new();
+        let mut owners: Vec<(&str, &str)> = Vec::new();
+        for account in &mut self.upstream.accounts {
+            if let Some(base_url) = &mut account.api_base_url {
+                *base_url = base_url.trim().trim_end_matches('/').to_string();
+            }
+        }
+        for account in &self.upstream.accounts {
+            let name = account.name.as_str();
+            if !is_identifier(name) {
+                problems.push(format!("upstream.accounts name may only contain letters, digits, '-' and '_' (got '{}')", name));
+            } else if name == DEFAULT_ACCOUNT || names.contains(&name) {
+                problems.push(format!("upstream.accounts name '{}' is reserved or used twice", name));
+            }
+            names.push(name);
+
+            if let Some(base_url) = &account.api_base_url {
+                if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
+                    problems.push(format!("upstream.accounts.{}.api_base_url must start with http:// or https:// (got '{}')", name, base_url));
+                }
+            }
+            if account.store_ids.is_empty() {
+                problems.push(format!("upstream.accounts.{} needs at least one store_id", name));
+            }
+            for store_id in &account.store_ids {
+                if !is_identifier(store_id) {
+                    problems.push(format!("upstream.accounts.{}.store_ids may only contain letters, digits, '-' and '_' (got '{}')", name, store_id));
+                } else if let Some((_, owner)) = owners.iter().find(|(s, _)| s == store_id) {
+                    problems.push(format!("Store '{}' belongs to both upstream accounts '{}' and '{}'", store_id, owner, name));
+                } else {
+                    owners.push((store_id, name));
+                }
+            }
+            if account.username.is_some() && account.password.is_none() {
+                problems.push(format!("upstream.accounts.{}.password is required when username is set", name));
+            }
+        }
+    }
+
+    /// Semua store yang dilayani: store default dulu, lalu `store_ids` dan store tiap akun (tanpa duplikat)
+    pub fn stores(&self) -> Vec<String> {
+        let mut stores = vec![self.upstream.store_id.clone()];
+        let accounts = self.upstream.accounts.iter().flat_map(|a| &a.store_ids);
+        for store_id in self.upstream.store_ids.iter().chain(accounts) {
+            if !stores.contains(store_id) {
+                stores.push(store_id.clone());
+            }
+        }
+        stores
+    }
+
+    pub fn is_default_store(&self, store_id: &str) -> bool {
+        store_id == self.upstream.store_id
+    }
+
+    /// `store_id` dari request; kosong = store default, selain itu harus ada di `STORE_ID`/`STORE_IDS`
+    pub fn resolve_store(&self, requested: Option<&str>) -> Result<String, DebugAppError> {
+        match requested.map(str::trim).filter(|s| !s.is_empty()) {
+            None => Ok(self.upstream.store_id.clone()),
+            Some(store_id) if self.stores().iter().any(|s| s == store_id) => Ok(store_id.to_string()),
+            Some(store_id) => Err(DebugAppError::InvalidInput(format!(
+                "Store '{}' is not configured (STORE_ID / STORE_IDS / upstream.accounts)",
+                store_id
+            ))),
+        }
+    }
+
+    fn account(&self, store_id: &str) -> Option<&UpstreamAccount> {
+        self.upstream.accounts.iter().find(|a| a.store_ids.iter().any(|s| s == store_id))
+    }
+
+    /// Nama akun yang login untuk store ini; `DEFAULT_ACCOUNT` kalau store tidak ada di `upstream.accounts`
+    pub fn account_for(&self, store_id: &str) -> &str {
+        self.account(store_id).map_or(DEFAULT_ACCOUNT, |a| a.name.as_str())
+    }
+
+    /// Nama akun default dulu, lalu `upstream.accounts`
+    pub fn account_names(&self) -> Vec<&str> {
+        std::iter::once(DEFAULT_ACCOUNT)
+            .chain(self.upstream.accounts.iter().map(|a| a.name.as_str()))
+            .collect()
+    }
+
+    /// Config upstream untuk fetch satu store: URL datatables memakai `store_id` ini, base URL dan
+    /// kredensial dari akun pemilik store
+    pub fn upstream_for(&self, store_id: &str) -> UpstreamConfig {
+        let upstream = UpstreamConfig {
+            store_id: store_id.to_string(),
+            accounts: Vec::new(),
+            ..self.upstream.clone()
+        };
+        match self.account(store_id) {
+            Some(account) => UpstreamConfig {
+                api_base_url: account.api_base_url.clone().unwrap_or(upstream.api_base_url.clone()),
+                username: account.username.clone(),
+                password: account.password.clone(),
+                cookie: account.cookie.clone(),
+                ..upstream
+            },
+            None => upstream,
+        }
+    }
+
+    /// Config upstream akun bernama (store default akun dipakai untuk URL datatables)
+    pub fn upstream_for_account(&self, name: &str) -> Option<UpstreamConfig> {
+        if name == DEFAULT_ACCOUNT {
+            return Some(UpstreamConfig { accounts: Vec::new(), ..self.upstream.clone() });
+        }
+        let account = self.upstream.accounts.iter().find(|a| a.name == name)?;
+        Some(self.upstream_for(account.store_ids.first()?))
+    }
+}
+
+/// Tampilan untuk log startup; password disamarkan
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let optional = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+        let secret = |value: &Option<String>| if value.is_some() { "***" } else { "-" };
+
+        writeln!(f, "server.host = {}", self.server.host)?;
+        writeln!(f, "server.port = {}", self.server.port)?;
+        writeln!(f, "server.log_level = {}", self.server.log_level)?;
+        writeln!(f, "server.log_format = {}", self.server.log_format)?;
+        writeln!(f, "server.shutdown_timeout_secs = {}", self.server.shutdown_timeout_secs)?;
+        writeln!(f, "server.admin_token = {}", secret(&self.server.admin_token))?;
+        writeln!(f, "upstream.api_base_url = {}", self.upstream.api_base_url)?;
+        writeln!(f, "upstream.store_id = {}", self.upstream.store_id)?;
+        writeln!(f, "upstream.store_ids = {}", if self.upstream.store_ids.is_empty() { "-".to_string() } else { self.upstream.store_ids.join(",") })?;
+        writeln!(f, "upstream.api_timestamp = {}", self.upstream.api_timestamp)?;
+        writeln!(f, "upstream.username = {}", optional(&self.upstream.username))?;
+        writeln!(f, "upstream.password = {}", secret(&self.upstream.password))?;
+        writeln!(f, "upstream.cookie = {}", secret(&self.upstream.cookie))?;
+        for account in &self.upstream.accounts {
+            writeln!(
+                f,
+                "upstream.accounts.{} = stores {}, {}, user {}, password {}, cookie {}",
+                account.name,
+                account.store_ids.join(","),
+                account.api_base_url.as_deref().unwrap_or(&self.upstream.api_base_url),
+                optional(&account.username),
+                secret(&account.password),
+                secret(&account.cookie),
+            )?;
+        }
+        writeln!(f, "upstream.page_delay_min_ms = {}", self.upstream.page_delay_min_ms)?;
+        writeln!(f, "upstream.page_delay_max_ms = {}", self.upstream.page_delay_max_ms)?;
+        writeln!(f, "cache.file_path = {}", self.cache.file_path)?;
+        writeln!(f, "cache.audit_log_file_path = {}", self.cache.audit_log_file_path)?;
+        writeln!(f, "cache.audit_log_max_entries = {}", self.cache.audit_log_max_entries)?;
+        writeln!(f, "cache.max_concurrent_jobs = {}", self.cache.max_concurrent_jobs)?;
+        writeln!(f, "cache.job_timeout_secs = {}", self.cache.job_timeout_secs)?;
+        writeln!(f, "cache.strict_load = {}", self.cache.strict_load)?;
+        writeln!(f, "cache.memory_budget_mb = {}", self.cache.memory_budget_mb)?;
+        writeln!(f, "cache.spill_dir = {}", self.cache.spill_dir)?;
+        writeln!(f, "export.csv_columns = {}", optional(&self.export.csv_columns))?;
+        writeln!(f, "export.csv_delimiter = {}", optional(&self.export.csv_delimiter))?;
+        writeln!(f, "export.csv_date_format = {}", optional(&self.export.csv_date_format))?;
+        writeln!(f, "export.parquet_export_dir = {}", self.export.parquet_export_dir)?;
+        writeln!(f, "export.pdf_template_path = {}", optional(&self.export.pdf_template_path))?;
+        writeln!(f, "telemetry.otlp_endpoint = {}", optional(&self.telemetry.otlp_endpoint))?;
+        write!(f, "telemetry.service_name = {}", self.telemetry.service_name)
+    }
+}
+
+/// Id store/akun jadi bagian nama file dan env, jadi dibatasi ke `[A-Za-z0-9_-]`
+fn is_identifier(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn env_value<T: FromStr>(problems: &mut Vec<String>, name: &str, target: &mut T) {
+    let Ok(raw) = env::var(name) else {
+        return;
+    };
+    match raw.trim().parse() {
+        Ok(value) => *target = value,
+        Err(_) => problems.push(format!("{} has an invalid value: '{}'", name, raw)),
+    }
+}
+
+/// Daftar dipisah koma, mis. `STORE_IDS=264,265`; kosong = daftar kosong
+fn env_list(name: &str, target: &mut Vec<String>) {
+    if let Ok(raw) = env::var(name) {
+        *target = raw.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect();
+    }
+}
+
+/// Nilai teks apa adanya (tanpa trim), mis. password atau delimiter tab
+fn env_option(name: &str, target: &mut Option<String>) {
+    if let Ok(value) = env::var(name) {
+        if !value.is_empty() {
+            *target = Some(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valid_config() -> Config {
+        let mut config = Config::default();
+        config.upstream.api_base_url = "https://kasir.example.com/".to_string();
+        config.upstream.store_id = "264".to_string();
+        config
+    }
+
+    fn problems(mut config: Config) -> Vec<String> {
+        let mut problems = Vec::new();
+        config.validate(&mut problems);
+        problems
+    }
+
+    #[test]
+    fn minimal_config_is_valid() {
+        assert!(problems(valid_config()).is_empty());
+    }
+
+    #[test]
+    fn csv_delimiter_accepts_the_same_aliases_as_export() {
+        for delimiter in [";", "tab", "\\t", "semicolon", "comma"] {
+            let mut config = valid_config();
+            config.export.csv_delimiter = Some(delimiter.to_string());
+            assert!(problems(config).is_empty(), "{}", delimiter);
+        }
+        let mut config = valid_config();
+        config.export.csv_delimiter = Some(";;".to_string());
+        assert!(problems(config).iter().any(|p| p.contains("CSV_DELIMITER")));
+    }
+
+    #[test]
+    fn default_store_id_must_be_an_identifier() {
+        for store_id in ["../264", "264|x", " 264"] {
+            let mut config = valid_config();
+            config.upstream.store_id = store_id.to_string();
+            assert!(problems(config).iter().any(|p| p.starts_with("STORE_ID may only contain")), "{}", store_id);
+        }
+        let mut config = valid_config();
+        config.upstream.store_id = "store-264_a".to_string();
+        assert!(problems(config).is_empty());
+    }
+
+    #[test]
+    fn collects_every_problem() {
+        let mut config = Config::default();
+        config.upstream.store_ids = vec!["../etc".to_string()];
+        config.cache.audit_log_max_entries = 0;
+        config.upstream.username = Some("kasir".to_string());
+        config.export.csv_columns = Some("no_nota,unknown".to_string());
+        let problems = problems(config);
+        for expected in ["API_BASE_URL", "STORE_ID is required", "STORE_IDS", "AUDIT_LOG_MAX_ENTRIES", "UPSTREAM_PASSWORD", "CSV_COLUMNS"] {
+            assert!(problems.iter().any(|p| p.contains(expected)), "missing {}: {:?}", expected, problems);
+        }
+    }
+}
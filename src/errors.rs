@@ -1,25 +1,45 @@
 use axum::{http::StatusCode, response::{IntoResponse, Response}, Json};
-use serde_json::json;
-use tracing::info;
+use serde::Serialize;
+use tracing::{error, info};
+use utoipa::ToSchema;
 
-#[derive(Debug)]
-pub struct AppError(pub reqwest::Error);
+/// Kode error yang stabil untuk client; dipakai frontend untuk branching, bukan `message`
+#[derive(Serialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    UpstreamRequestFailed,
+    UpstreamInvalidResponse,
+    InvalidDateFormat,
+    SerializationFailed,
+    FileWriteFailed,
+    Unauthorized,
+    InvalidInput,
+    NotFound,
+    Conflict,
+    ServiceBusy,
+    Timeout,
+}
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let error_message = self.0.to_string();
-        info!("Error occurred: {}", error_message);
-        let body = Json(json!({
-            "error": "Failed to process request",
-            "details": error_message,
-        }));
-        (StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
-    }
+#[derive(Serialize, ToSchema, Debug)]
+pub struct ErrorBody {
+    pub code: ErrorCode,
+    pub message: String,
 }
 
-impl From<reqwest::Error> for AppError {
-    fn from(error: reqwest::Error) -> Self {
-        AppError(error)
+/// Envelope error untuk semua endpoint: `{"error": {"code": "...", "message": "..."}}`
+#[derive(Serialize, ToSchema, Debug)]
+pub struct ErrorResponse {
+    pub error: ErrorBody,
+}
+
+impl ErrorResponse {
+    fn into_response_with(self, status: StatusCode) -> Response {
+        if status.is_server_error() {
+            error!("[ERROR] {:?}: {}", self.error.code, self.error.message);
+        } else {
+            info!("[ERROR] {:?}: {}", self.error.code, self.error.message);
+        }
+        (status, Json(self)).into_response()
     }
 }
 
@@ -31,43 +51,76 @@ pub enum DebugAppError {
     Serialization(String),
     FileWrite(String),
     Unauthorized(String),
+    InvalidInput(String),
+    NotFound(String),
+    /// Operasi yang sama sedang berjalan (mis. operasi admin single-instance)
+    Conflict(String),
+    /// Job upstream yang berjalan sudah mencapai `MAX_CONCURRENT_JOBS`
+    ServiceBusy(String),
+    /// Job upstream melewati `JOB_TIMEOUT_SECS`
+    Timeout(String),
+}
+
+impl DebugAppError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            DebugAppError::Reqwest(_) => ErrorCode::UpstreamRequestFailed,
+            DebugAppError::Serde(_) => ErrorCode::UpstreamInvalidResponse,
+            DebugAppError::DateParse(_) => ErrorCode::InvalidDateFormat,
+            DebugAppError::Serialization(_) => ErrorCode::SerializationFailed,
+            DebugAppError::FileWrite(_) => ErrorCode::FileWriteFailed,
+            DebugAppError::Unauthorized(_) => ErrorCode::Unauthorized,
+            DebugAppError::InvalidInput(_) => ErrorCode::InvalidInput,
+            DebugAppError::NotFound(_) => ErrorCode::NotFound,
+            DebugAppError::Conflict(_) => ErrorCode::Conflict,
+            DebugAppError::ServiceBusy(_) => ErrorCode::ServiceBusy,
+            DebugAppError::Timeout(_) => ErrorCode::Timeout,
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            DebugAppError::Reqwest(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            DebugAppError::Serde(_) => StatusCode::BAD_GATEWAY,
+            DebugAppError::DateParse(_) => StatusCode::BAD_REQUEST,
+            DebugAppError::Serialization(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            DebugAppError::FileWrite(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            DebugAppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            DebugAppError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            DebugAppError::NotFound(_) => StatusCode::NOT_FOUND,
+            DebugAppError::Conflict(_) => StatusCode::CONFLICT,
+            DebugAppError::ServiceBusy(_) => StatusCode::SERVICE_UNAVAILABLE,
+            DebugAppError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            DebugAppError::Reqwest(e) => format!("Upstream request failed: {}", e),
+            DebugAppError::Serde(e) => format!("Failed to parse response from upstream: {}", e),
+            DebugAppError::DateParse(e)
+            | DebugAppError::Serialization(e)
+            | DebugAppError::FileWrite(e)
+            | DebugAppError::Unauthorized(e)
+            | DebugAppError::InvalidInput(e)
+            | DebugAppError::NotFound(e)
+            | DebugAppError::Conflict(e)
+            | DebugAppError::ServiceBusy(e)
+            | DebugAppError::Timeout(e) => e.clone(),
+        }
+    }
 }
 
 impl IntoResponse for DebugAppError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            DebugAppError::Reqwest(e) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Reqwest error: {}", e),
-            ),
-            DebugAppError::Serde(e) => (
-                StatusCode::BAD_GATEWAY,
-                format!("Failed to parse response from upstream: {}", e),
-            ),
-            DebugAppError::DateParse(e) => (
-                StatusCode::BAD_REQUEST,
-                format!("Date parse error: {}", e),
-            ),
-            DebugAppError::Serialization(e) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Serialization error: {}", e),
-            ),
-            DebugAppError::FileWrite(e) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("File write error: {}", e),
-            ),
-            DebugAppError::Unauthorized(e) => (
-                StatusCode::UNAUTHORIZED,
-                format!("Unauthorized: {}", e),
-            ),
-        };
-
-        info!("[DEBUG] Error occurred: {}", error_message);
-        let body = Json(json!({
-            "error": "An error occurred in the debugging function",
-            "details": error_message,
-        }));
-        (status, body).into_response()
+        let status = self.status();
+        ErrorResponse {
+            error: ErrorBody {
+                code: self.code(),
+                message: self.message(),
+            },
+        }
+        .into_response_with(status)
     }
 }
 
@@ -81,4 +134,4 @@ impl From<serde_json::Error> for DebugAppError {
     fn from(error: serde_json::Error) -> Self {
         DebugAppError::Serde(error)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,255 @@
+use axum::{
+    body::Bytes,
+    extract::{Query, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sha2::{Digest, Sha256};
+use crate::errors::{DebugAppError, ErrorResponse};
+use crate::models::{AdminCacheResponse, AdminOperationResponse, CacheImportResponse, ImportQuery, RangeQuery, StoreQuery};
+use crate::services::{AdminService, ArchiveService};
+use crate::state::AppState;
+use tracing::{info, warn};
+
+/// Middleware untuk semua route `/admin/*`: butuh `Authorization: Bearer <ADMIN_TOKEN>`.
+/// Tanpa `ADMIN_TOKEN` di config, endpoint admin selalu ditolak.
+pub async fn require_admin_token(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, DebugAppError> {
+    let Some(expected) = state.config().server.admin_token.clone() else {
+        return Err(DebugAppError::Unauthorized("Admin endpoints are disabled, set ADMIN_TOKEN to enable them".to_string()));
+    };
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or("");
+
+    if !token_matches(provided, &expected) {
+        warn!(uri = %request.uri(), "[ADMIN] Rejected request with missing or invalid admin token");
+        return Err(DebugAppError::Unauthorized("Missing or invalid admin token".to_string()));
+    }
+    Ok(next.run(request).await)
+}
+
+/// Bandingkan hash SHA-256 dengan waktu konstan supaya token tidak bisa ditebak dari latency
+fn token_matches(provided: &str, expected: &str) -> bool {
+    let provided = Sha256::digest(provided.as_bytes());
+    let expected = Sha256::digest(expected.as_bytes());
+    provided.iter().zip(expected.iter()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/cache",
+    tag = "admin",
+    params(RangeQuery, StoreQuery),
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Cached dates with counts and totals", body = AdminCacheResponse),
+        (status = 400, description = "Invalid or incomplete from/to, or unknown store", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 409, description = "Operation already running", body = ErrorResponse),
+    )
+)]
+pub async fn admin_list_cache(
+    State(state): State<AppState>,
+    Query(query): Query<RangeQuery>,
+    Query(store): Query<StoreQuery>,
+) -> Result<Json<AdminCacheResponse>, DebugAppError> {
+    let store = state.config().resolve_store(store.store_id.as_deref())?;
+    let dates = AdminService::exclusive(&state, "list_cache", || {
+        AdminService::list_cached_dates(&state, &store, query.from.as_deref(), query.to.as_deref())
+    }).await?;
+
+    Ok(Json(AdminCacheResponse {
+        status: "ok".to_string(),
+        store_id: store,
+        total_dates: dates.len(),
+        total_transactions: dates.iter().map(|d| d.transactions).sum(),
+        dates,
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/cache",
+    tag = "admin",
+    params(RangeQuery, StoreQuery),
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Dates evicted from memory and the cache file", body = AdminOperationResponse),
+        (status = 400, description = "Invalid or missing from/to, or unknown store", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 409, description = "Operation already running", body = ErrorResponse),
+    )
+)]
+pub async fn admin_evict_cache(
+    State(state): State<AppState>,
+    Query(query): Query<RangeQuery>,
+    Query(store): Query<StoreQuery>,
+) -> Result<Json<AdminOperationResponse>, DebugAppError> {
+    let (Some(from), Some(to)) = (query.from.as_deref(), query.to.as_deref()) else {
+        return Err(DebugAppError::InvalidInput("Both from and to are required to evict a range".to_string()));
+    };
+    let store = state.config().resolve_store(store.store_id.as_deref())?;
+    info!(store = %store, from = %from, to = %to, "[ADMIN] Evict requested");
+
+    let evicted = AdminService::exclusive(&state, "evict_cache", || AdminService::evict_range(&state, &store, from, to)).await?;
+    Ok(Json(AdminOperationResponse {
+        status: "ok".to_string(),
+        operation: "evict_cache".to_string(),
+        affected: evicted.len(),
+        message: format!("Evicted {} cached dates of store {} between {} and {}", evicted.len(), store, from, to),
+        dates: evicted,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/cache/clean-empty",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Empty dates removed", body = AdminOperationResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 409, description = "Operation already running", body = ErrorResponse),
+    )
+)]
+pub async fn admin_clean_empty_cache(
+    State(state): State<AppState>,
+) -> Result<Json<AdminOperationResponse>, DebugAppError> {
+    let (removed, cleaned_in_file) = AdminService::exclusive(&state, "clean_empty_cache", || AdminService::clean_empty(&state)).await?;
+    Ok(Json(AdminOperationResponse {
+        status: "ok".to_string(),
+        operation: "clean_empty_cache".to_string(),
+        affected: removed.len().max(cleaned_in_file),
+        message: format!(
+            "Removed {} empty dates from memory and {} from the cache file",
+            removed.len(),
+            cleaned_in_file
+        ),
+        dates: removed,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/cache/flush",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Memory cache of every store written to its cache file", body = AdminOperationResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 409, description = "Operation already running", body = ErrorResponse),
+        (status = 500, description = "Cache file could not be written", body = ErrorResponse),
+    )
+)]
+pub async fn admin_flush_cache(
+    State(state): State<AppState>,
+) -> Result<Json<AdminOperationResponse>, DebugAppError> {
+    let dates = AdminService::exclusive(&state, "flush_cache", || AdminService::flush(&state)).await?;
+    Ok(Json(AdminOperationResponse {
+        status: "ok".to_string(),
+        operation: "flush_cache".to_string(),
+        affected: dates,
+        message: format!("Saved {} dates to the cache file and spill dir", dates),
+        dates: Vec::new(),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/processing/clear",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Stuck processing flags cleared", body = AdminOperationResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 409, description = "Operation already running", body = ErrorResponse),
+    )
+)]
+pub async fn admin_clear_processing(
+    State(state): State<AppState>,
+) -> Result<Json<AdminOperationResponse>, DebugAppError> {
+    let cleared = AdminService::exclusive(&state, "clear_processing", || async {
+        Ok(state.cleanup_stuck_processing().await)
+    }).await?;
+    Ok(Json(AdminOperationResponse {
+        status: "ok".to_string(),
+        operation: "clear_processing".to_string(),
+        affected: cleared,
+        message: format!("Cleared {} processing flags", cleared),
+        dates: Vec::new(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/cache/export",
+    tag = "admin",
+    params(RangeQuery, StoreQuery),
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "`.tar.gz` with `manifest.json` and `cache.json`", content_type = "application/gzip", body = Vec<u8>),
+        (status = 400, description = "Invalid or incomplete from/to, or unknown store", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 409, description = "Operation already running", body = ErrorResponse),
+    )
+)]
+pub async fn admin_export_cache(
+    State(state): State<AppState>,
+    Query(query): Query<RangeQuery>,
+    Query(store): Query<StoreQuery>,
+) -> Result<Response, DebugAppError> {
+    let store = state.config().resolve_store(store.store_id.as_deref())?;
+    let (manifest, bytes) = AdminService::exclusive(&state, "export_cache", || {
+        ArchiveService::export(&state, &store, query.from.as_deref(), query.to.as_deref())
+    }).await?;
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "application/gzip".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", ArchiveService::filename(&manifest))),
+        ],
+        bytes,
+    ).into_response())
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/cache/import",
+    tag = "admin",
+    params(ImportQuery),
+    request_body(content = Vec<u8>, description = "Archive from `/admin/cache/export` or `cache export`", content_type = "application/gzip"),
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Archive validated and merged into the cache", body = CacheImportResponse),
+        (status = 400, description = "Invalid archive, checksum mismatch, unsupported schema, or a store that is not configured or differs from `store_id`", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 409, description = "Operation already running", body = ErrorResponse),
+    )
+)]
+pub async fn admin_import_cache(
+    State(state): State<AppState>,
+    Query(query): Query<ImportQuery>,
+    body: Bytes,
+) -> Result<Json<CacheImportResponse>, DebugAppError> {
+    let policy = query.policy.unwrap_or_default();
+    info!(bytes = body.len(), policy = ?policy, "[ADMIN] Cache import requested");
+
+    let (manifest, summary) = AdminService::exclusive(&state, "import_cache", || {
+        ArchiveService::import(&state, body.to_vec(), policy, query.store_id.as_deref())
+    }).await?;
+    Ok(Json(CacheImportResponse {
+        status: "ok".to_string(),
+        manifest,
+        summary,
+    }))
+}
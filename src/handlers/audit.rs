@@ -0,0 +1,52 @@
+use axum::{Json, extract::{Query, State}};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use crate::errors::{DebugAppError, ErrorResponse};
+use crate::models::{AuditChangesResponse, ChangeKind, ChangeSummary, StoreQuery};
+use crate::services::AuditService;
+use crate::state::AppState;
+use tracing::info;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub no_nota: Option<String>,
+    pub kind: Option<ChangeKind>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/audit/changes",
+    tag = "audit",
+    params(AuditQuery, StoreQuery),
+    responses(
+        (status = 200, description = "Edits/voids detected by force refresh", body = AuditChangesResponse),
+        (status = 400, description = "Invalid from/to date or unknown store", body = ErrorResponse),
+    )
+)]
+pub async fn get_audit_changes(
+    State(state): State<AppState>,
+    Query(query): Query<AuditQuery>,
+    Query(store): Query<StoreQuery>,
+) -> Result<Json<AuditChangesResponse>, DebugAppError> {
+    info!("[AUDIT] Query: {:?}", query);
+    let store = state.config().resolve_store(store.store_id.as_deref())?;
+
+    let changes = AuditService::query(
+        &state,
+        &store,
+        query.from.as_deref(),
+        query.to.as_deref(),
+        query.no_nota.as_deref(),
+        query.kind,
+    ).await?;
+
+    Ok(Json(AuditChangesResponse {
+        status: "ok".to_string(),
+        total: changes.len(),
+        summary: ChangeSummary::from_entries(&changes),
+        changes,
+    }))
+}
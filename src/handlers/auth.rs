@@ -1,67 +1,93 @@
-use axum::{http::StatusCode, Json, extract::State, debug_handler};
+use axum::{Json, extract::{Query, State}, debug_handler};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use utoipa::ToSchema;
+use crate::errors::{DebugAppError, ErrorResponse};
+use crate::models::{LoginStatusResponse, StoreQuery};
 use crate::state::AppState;
 use crate::services::AuthService;
 use tracing::{info, error};
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
 pub struct LoginRequest {
     pub username: String,
     pub password: String,
+    /// Login ke akun upstream pemilik store ini; kosong = store default
+    #[serde(default)]
+    pub store_id: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/login",
+    tag = "auth",
+    params(StoreQuery),
+    responses(
+        (status = 200, description = "Upstream session of the store's account is valid", body = LoginStatusResponse),
+        (status = 400, description = "Unknown store", body = ErrorResponse),
+        (status = 401, description = "Session expired or invalid cookie", body = ErrorResponse),
+    )
+)]
 #[debug_handler]
 pub async fn get_login_status(
     State(state): State<AppState>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    let is_unauthorized = state.is_unauthorized().await;
-    
-    if is_unauthorized {
-        (StatusCode::UNAUTHORIZED, Json(json!({
-            "status": "unauthorized",
-            "message": "Session expired or invalid cookie"
-        })))
-    } else {
-        (StatusCode::OK, Json(json!({
-            "status": "authorized",
-            "message": "Session is valid"
-        })))
+    Query(store): Query<StoreQuery>,
+) -> Result<Json<LoginStatusResponse>, DebugAppError> {
+    let config = state.config();
+    let store = config.resolve_store(store.store_id.as_deref())?;
+    if state.is_unauthorized(config.account_for(&store)).await {
+        return Err(DebugAppError::Unauthorized("Session expired or invalid cookie".to_string()));
     }
+
+    Ok(Json(LoginStatusResponse {
+        status: "authorized".to_string(),
+        message: "Session is valid".to_string(),
+        cookie: None,
+    }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Logged in (or already logged in)", body = LoginStatusResponse),
+        (status = 400, description = "Unknown store", body = ErrorResponse),
+        (status = 401, description = "Login rejected by upstream", body = ErrorResponse),
+        (status = 500, description = "Upstream login page unreachable", body = ErrorResponse),
+    )
+)]
 #[debug_handler]
 pub async fn post_login(
     State(state): State<AppState>,
     Json(login_req): Json<LoginRequest>,
-) -> (StatusCode, Json<serde_json::Value>) {
+) -> Result<Json<LoginStatusResponse>, DebugAppError> {
+    let config = state.config();
+    let store = config.resolve_store(login_req.store_id.as_deref())?;
+    let account = config.account_for(&store);
+
     // If already authorized, return success
-    if !state.is_unauthorized().await {
-        return (StatusCode::OK, Json(json!({
-            "status": "authorized",
-            "message": "Already logged in"
-        })));
+    if !state.is_unauthorized(account).await {
+        return Ok(Json(LoginStatusResponse {
+            status: "authorized".to_string(),
+            message: "Already logged in".to_string(),
+            cookie: None,
+        }));
     }
 
     // Perform login with credentials from frontend
-    match AuthService::perform_login(&login_req.username, &login_req.password).await {
-        Ok(cookie) => {
-            // Set authorized state
-            state.set_unauthorized(false).await;
-            
-            info!("[AUTH] Login successful for user: {}", login_req.username);
-            (StatusCode::OK, Json(json!({
-                "status": "authorized",
-                "message": "Login successful",
-                "cookie": cookie
-            })))
-        }
-        Err(e) => {
-            error!("[AUTH] Login failed: {:?}", e);
-            (StatusCode::UNAUTHORIZED, Json(json!({
-                "status": "unauthorized",
-                "message": format!("Login failed: {:?}", e)
-            })))
-        }
-    }
-}
\ No newline at end of file
+    let cookie = AuthService::perform_login(&config.upstream_for(&store), &login_req.username, &login_req.password)
+        .await
+        .inspect_err(|e| error!(account = %account, "[AUTH] Login failed: {:?}", e))?;
+
+    // Set authorized state. Cookie hanya untuk client ini, bukan sesi server: sesi server
+    // (GET /transactions) hanya dari UPSTREAM_USERNAME / akun di `upstream.accounts`
+    state.set_unauthorized(account, false).await;
+
+    info!(account = %account, "[AUTH] Login successful for user: {}", login_req.username);
+    Ok(Json(LoginStatusResponse {
+        status: "authorized".to_string(),
+        message: "Login successful".to_string(),
+        cookie: Some(cookie),
+    }))
+}
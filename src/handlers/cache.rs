@@ -1,55 +1,47 @@
-use axum::{http::StatusCode, response::IntoResponse, Json, extract::State};
-use serde_json::json;
-use crate::models::{Payload, response::{TransaksiResponse, CachedDataResponse}};
-use crate::services::{cache_service::CacheService, DateService, TransactionService};
+use axum::{http::{request::Parts, HeaderMap, StatusCode}, response::{IntoResponse, Response}, Json, extract::{Path, Query, State}};
+use crate::models::{ChangeSummary, ExportFormat, ExportQuery, ForceRefreshResponse, Payload, RangeQuery, StoreQuery, Transaksi, TransactionFilter, response::{TransaksiResponse, CachedDataResponse}};
+use crate::services::{cache_service::CacheService, AuditService, AuthService, SmartFetchResult, DateService, EtagService, ExportService, FilterService, StreamService, TransactionService};
+use super::export;
 use crate::state::AppState;
-use crate::errors::DebugAppError;
+use crate::errors::{DebugAppError, ErrorResponse};
 use tracing::{info, error};
 
 
 
+#[utoipa::path(
+    post,
+    path = "/force-refresh",
+    tag = "cache",
+    params(StoreQuery),
+    request_body = Payload,
+    responses(
+        (status = 200, description = "Cache for the range replaced with fresh upstream data", body = ForceRefreshResponse),
+        (status = 400, description = "Missing cookie, invalid date or unknown store", body = ErrorResponse),
+        (status = 401, description = "Session expired or invalid cookie", body = ErrorResponse),
+        (status = 500, description = "Upstream or storage failure", body = ErrorResponse),
+        (status = 503, description = "`MAX_CONCURRENT_JOBS` upstream fetches already running", body = ErrorResponse),
+        (status = 504, description = "Upstream fetch exceeded `JOB_TIMEOUT_SECS`", body = ErrorResponse),
+    )
+)]
 pub async fn force_refresh_data(
     State(state): State<AppState>,
+    Query(store): Query<StoreQuery>,
     Json(payload): Json<Payload>,
-) -> impl IntoResponse {
+) -> Result<Json<ForceRefreshResponse>, DebugAppError> {
     // Validasi payload
     if payload.cookie.is_empty() {
-        return (StatusCode::BAD_REQUEST, Json(json!({
-            "success": false,
-            "message": "Cookie is required"
-        })));
+        return Err(DebugAppError::InvalidInput("Cookie is required".to_string()));
     }
+    let config = state.config();
+    let store = config.resolve_store(store.store_id.as_deref())?;
 
     // Normalize dates
-    let from_normalized = match DateService::normalize_date_for_api(&payload.from) {
-        Ok(d) => d,
-        Err(_) => {
-            return (StatusCode::BAD_REQUEST, Json(json!({
-                "success": false,
-                "message": "Invalid from date format"
-            })));
-        }
-    };
-    let to_normalized = match DateService::normalize_date_for_api(&payload.to) {
-        Ok(d) => d,
-        Err(_) => {
-            return (StatusCode::BAD_REQUEST, Json(json!({
-                "success": false,
-                "message": "Invalid to date format"
-            })));
-        }
-    };
+    let from_normalized = DateService::normalize_date_for_api(&payload.from)
+        .map_err(|_| DebugAppError::DateParse("Invalid from date format".to_string()))?;
+    let to_normalized = DateService::normalize_date_for_api(&payload.to)
+        .map_err(|_| DebugAppError::DateParse("Invalid to date format".to_string()))?;
 
-    info!("[FORCE_REFRESH] Request: {} to {}", from_normalized, to_normalized);
-
-    // Clear cache for this range
-    if let Ok(dates) = DateService::get_date_range(&from_normalized, &to_normalized) {
-        let mut cache_write = state.cache.write().await;
-        for date in &dates {
-            cache_write.remove(date);
-        }
-        info!("[FORCE_REFRESH] Cleared {} dates from cache", dates.len());
-    }
+    info!("[FORCE_REFRESH] Request: {} to {} (store {})", from_normalized, to_normalized, store);
 
     // Fetch directly with 2-loop (synchronous)
     let refresh_payload = Payload {
@@ -58,229 +50,364 @@ pub async fn force_refresh_data(
         cookie: payload.cookie.clone(),
     };
 
-    match TransactionService::fetch_direct_two_loops(&refresh_payload).await {
-        Ok(response) => {
-            if !response.data.is_empty() {
-                let range_dates = DateService::get_date_range(&from_normalized, &to_normalized).unwrap();
-                let mut cache = state.cache.write().await;
-                let mut total_cached = 0;
-                
-                for date in range_dates {
-                    let date_data: Vec<_> = response.data.iter()
-                        .filter(|t| {
-                            if let Ok(normalized) = DateService::normalize_date_for_api(&t.tanggal_transaksi) {
-                                normalized == date
-                            } else {
-                                false
-                            }
-                        })
-                        .cloned()
-                        .collect();
-                    if !date_data.is_empty() {
-                        cache.insert(date, date_data);
-                        total_cached += 1;
-                    }
-                }
-                info!("[FORCE_REFRESH] Cached {} dates, {} transactions", total_cached, response.data.len());
-                drop(cache);
-                
-                if let Err(e) = CacheService::save_cache_to_file(&state).await {
-                    error!("[FORCE_REFRESH] Failed to save cache: {:?}", e);
-                }
-            }
+    // Tercatat sebagai job (readiness, drain shutdown), dibatasi MAX_CONCURRENT_JOBS dan JOB_TIMEOUT_SECS
+    state
+        .run_job(&store, &refresh_payload, refresh_range(&state, &store, &refresh_payload))
+        .await
+        .map(Json)
+}
 
-            state.set_unauthorized(false).await;
-            (StatusCode::OK, Json(json!({
-                "success": true,
-                "message": "Cache refreshed successfully",
-                "total_transactions": response.data.len()
-            })))
+/// Fetch ulang range dari upstream, ganti isi cache range itu dan catat perubahan di audit log
+async fn refresh_range(state: &AppState, store: &str, refresh_payload: &Payload) -> Result<ForceRefreshResponse, DebugAppError> {
+    let config = state.config();
+    let response = match TransactionService::fetch_direct_two_loops(&config.upstream_for(store), refresh_payload).await {
+        Ok(response) => {
+            state.record_upstream_success().await;
+            response
         }
         Err(e) => {
             if matches!(e, DebugAppError::Unauthorized(_)) {
-                state.set_unauthorized(true).await;
-                return (StatusCode::UNAUTHORIZED, Json(json!({
-                    "success": false,
-                    "message": "Session expired or invalid cookie"
-                })));
+                state.set_unauthorized(config.account_for(store), true).await;
+                return Err(DebugAppError::Unauthorized("Session expired or invalid cookie".to_string()));
             }
             error!("[FORCE_REFRESH] Failed: {:?}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
-                "success": false,
-                "message": "Failed to refresh cache"
-            })))
+            return Err(e);
         }
+    };
+
+    let range_dates = DateService::get_date_range(&refresh_payload.from, &refresh_payload.to)
+        .map_err(|_| DebugAppError::DateParse("Invalid date range".to_string()))?;
+    let replaced = CacheService::replace_range(state, store, range_dates, &response.data).await;
+    info!("[FORCE_REFRESH] Replaced {} dates, {} transactions, {} duplikat digabung, {} tanggal kosong dilewati",
+        replaced.replaced, response.data.len(), replaced.duplicates_collapsed, replaced.empty_dates);
+    let changes = replaced.changes;
+
+    let change_summary = ChangeSummary::from_entries(&changes);
+    if !changes.is_empty() {
+        info!("[FORCE_REFRESH] Detected changes: {:?}", change_summary);
+    }
+    if let Err(e) = AuditService::record_changes(state, changes).await {
+        error!("[FORCE_REFRESH] Failed to record audit log: {:?}", e);
+    }
+
+    if let Err(e) = CacheService::save_cache_to_file(state).await {
+        error!("[FORCE_REFRESH] Failed to save cache: {:?}", e);
     }
+
+    state.set_unauthorized(config.account_for(store), false).await;
+    Ok(ForceRefreshResponse {
+        success: true,
+        message: "Cache refreshed successfully".to_string(),
+        total_transactions: response.data.len(),
+        total_tagihan: response.total_tagihan,
+        parse_errors: response.parse_errors,
+        duplicates_collapsed: replaced.duplicates_collapsed,
+        changes: change_summary,
+    })
 }
 
+#[utoipa::path(
+    post,
+    path = "/data-cached",
+    tag = "transactions",
+    params(StoreQuery, ExportQuery, TransactionFilter),
+    request_body = Payload,
+    responses(
+        (status = 200, description = "Transactions (JSON), a file for `?format=csv|xlsx|parquet`, or a stream for `?stream=`", content(
+            (CachedDataResponse = "application/json"),
+            (String = "text/csv"),
+            (Vec<u8> = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+            (Vec<u8> = "application/vnd.apache.parquet"),
+            (String = "application/x-ndjson"),
+        )),
+        (status = 304, description = "`If-None-Match` matches the current ETag"),
+        (status = 400, description = "Invalid date, filter, export option or unknown store", body = ErrorResponse),
+        (status = 401, description = "Session expired or invalid cookie", body = ErrorResponse),
+        (status = 500, description = "Upstream or export failure", body = ErrorResponse),
+        (status = 503, description = "`MAX_CONCURRENT_JOBS` upstream fetches already running", body = ErrorResponse),
+        (status = 504, description = "Upstream fetch exceeded `JOB_TIMEOUT_SECS`", body = ErrorResponse),
+    )
+)]
 pub async fn get_cached_data(
     State(state): State<AppState>,
+    parts: Parts,
+    Query(store): Query<StoreQuery>,
+    Query(export_query): Query<ExportQuery>,
+    Query(filter): Query<TransactionFilter>,
     Json(payload): Json<Payload>,
-) -> impl IntoResponse {
+) -> Response {
     state.cleanup_old_jobs().await;
-
-    // Normalize dates to DD/MM/YYYY format first
-    let from_normalized = match DateService::normalize_date_for_api(&payload.from) {
-        Ok(d) => d,
-        Err(_) => {
-            return (StatusCode::BAD_REQUEST, Json(json!({
-                "status": "invalid_date_format",
-                "message": "Invalid from date format"
-            })));
-        }
-    };
-    let to_normalized = match DateService::normalize_date_for_api(&payload.to) {
-        Ok(d) => d,
-        Err(_) => {
-            return (StatusCode::BAD_REQUEST, Json(json!({
-                "status": "invalid_date_format",
-                "message": "Invalid to date format"
-            })));
-        }
+    let store = match state.config().resolve_store(store.store_id.as_deref()) {
+        Ok(store) => store,
+        Err(e) => return e.into_response(),
     };
 
-    info!("[CACHE_CHECK] Request: {} to {} -> Normalized: {} to {}", 
-        payload.from, payload.to, from_normalized, to_normalized);
+    // ?stream=ndjson|array: kirim per tanggal langsung dari cache, tanpa wrapper/ringkasan
+    if let Some(mode) = StreamService::requested_mode(&parts.headers, &export_query) {
+        let row_filter = match export::stream_filter(&export_query, &filter) {
+            Ok(row_filter) => row_filter,
+            Err(e) => return e.into_response(),
+        };
+        return match CacheService::fill_missing_dates(&state, &store, &payload).await {
+            Ok(fill) => export::stream_response(&state, fill, row_filter, mode),
+            Err(e) => e.into_response(),
+        };
+    }
+
+    match CacheService::get_range_with_smart_fetch(&state, &store, &payload).await {
+        Ok(result) => cached_data_response(&state, &parts, &export_query, &filter, &payload, result).await,
+        Err(e) => e.into_response(),
+    }
+}
 
-    // Get date range using normalized dates
-    let dates = match DateService::get_date_range(&from_normalized, &to_normalized) {
-        Ok(d) => d,
-        Err(_) => {
-            return (StatusCode::BAD_REQUEST, Json(json!({
-                "status": "invalid_date_range",
-                "message": "Invalid date format"
-            })));
-        }
+/// Read API: `GET /transactions?from=...&to=...`, memakai cookie sesi server (bukan dari client)
+#[utoipa::path(
+    get,
+    path = "/transactions",
+    tag = "transactions",
+    params(RangeQuery, StoreQuery, ExportQuery, TransactionFilter),
+    responses(
+        (status = 200, description = "Transactions (JSON), a file for `?format=csv|xlsx|parquet`, or a stream for `?stream=`", content(
+            (CachedDataResponse = "application/json"),
+            (String = "text/csv"),
+            (Vec<u8> = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+            (Vec<u8> = "application/vnd.apache.parquet"),
+            (String = "application/x-ndjson"),
+        )),
+        (status = 304, description = "`If-None-Match` matches the current ETag"),
+        (status = 400, description = "Invalid date, filter, export option or unknown store", body = ErrorResponse),
+        (status = 401, description = "Session expired or invalid cookie", body = ErrorResponse),
+        (status = 500, description = "Upstream or export failure", body = ErrorResponse),
+        (status = 503, description = "`MAX_CONCURRENT_JOBS` upstream fetches already running", body = ErrorResponse),
+        (status = 504, description = "Upstream fetch exceeded `JOB_TIMEOUT_SECS`", body = ErrorResponse),
+    )
+)]
+pub async fn get_transactions(
+    State(state): State<AppState>,
+    parts: Parts,
+    Query(range): Query<RangeQuery>,
+    Query(store): Query<StoreQuery>,
+    Query(export_query): Query<ExportQuery>,
+    Query(filter): Query<TransactionFilter>,
+) -> Response {
+    let (Some(from), Some(to)) = (range.from, range.to) else {
+        return DebugAppError::InvalidInput("Both from and to are required".to_string()).into_response();
     };
+    read_range(&state, &parts, ReadRangeRequest { store, export_query, filter, from, to }).await
+}
 
-    info!("[CACHE_CHECK] Checking {} dates in cache", dates.len());
+/// Read API: `GET /transactions/{date}` (mis. `2025-10-01` atau `01-10-2025`)
+#[utoipa::path(
+    get,
+    path = "/transactions/{date}",
+    tag = "transactions",
+    params(("date" = String, Path, description = "Date as YYYY-MM-DD or DD-MM-YYYY"), StoreQuery, ExportQuery, TransactionFilter),
+    responses(
+        (status = 200, description = "Transactions (JSON), a file for `?format=csv|xlsx|parquet`, or a stream for `?stream=`", content(
+            (CachedDataResponse = "application/json"),
+            (String = "text/csv"),
+            (Vec<u8> = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+            (Vec<u8> = "application/vnd.apache.parquet"),
+            (String = "application/x-ndjson"),
+        )),
+        (status = 304, description = "`If-None-Match` matches the current ETag"),
+        (status = 400, description = "Invalid date, filter, export option or unknown store", body = ErrorResponse),
+        (status = 401, description = "Session expired or invalid cookie", body = ErrorResponse),
+        (status = 500, description = "Upstream or export failure", body = ErrorResponse),
+        (status = 503, description = "`MAX_CONCURRENT_JOBS` upstream fetches already running", body = ErrorResponse),
+        (status = 504, description = "Upstream fetch exceeded `JOB_TIMEOUT_SECS`", body = ErrorResponse),
+    )
+)]
+pub async fn get_transactions_by_date(
+    State(state): State<AppState>,
+    parts: Parts,
+    Path(date): Path<String>,
+    Query(store): Query<StoreQuery>,
+    Query(export_query): Query<ExportQuery>,
+    Query(filter): Query<TransactionFilter>,
+) -> Response {
+    let (from, to) = (date.clone(), date);
+    read_range(&state, &parts, ReadRangeRequest { store, export_query, filter, from, to }).await
+}
 
-    // Check cache and find missing dates
-    let mut missing_dates = Vec::new();
-    let mut cached_data = Vec::new();
-    {
-        let cache = state.cache.read().await;
-        for date in &dates {
-            if let Some(transactions) = cache.get(date) {
-                info!("[CACHE_HIT] Found {} transactions for {}", transactions.len(), date);
-                cached_data.extend(transactions.clone());
-            } else {
-                info!("[CACHE_MISS] No data for {}", date);
-                missing_dates.push(date.clone());
-            }
-        }
+/// Read API: `GET /transactions/by-nota/{no_nota}`, cari di seluruh cache tanpa request ke upstream
+#[utoipa::path(
+    get,
+    path = "/transactions/by-nota/{no_nota}",
+    tag = "transactions",
+    params(("no_nota" = String, Path, description = "Receipt number"), StoreQuery),
+    responses(
+        (status = 200, description = "Cached transactions with this nota", body = TransaksiResponse),
+        (status = 304, description = "`If-None-Match` matches the current ETag"),
+        (status = 400, description = "Unknown store", body = ErrorResponse),
+        (status = 404, description = "Nota not found in cache", body = ErrorResponse),
+        (status = 500, description = "A date spilled to disk could not be read", body = ErrorResponse),
+    )
+)]
+pub async fn get_transaction_by_nota(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(no_nota): Path<String>,
+    Query(store): Query<StoreQuery>,
+) -> Response {
+    let store = match state.config().resolve_store(store.store_id.as_deref()) {
+        Ok(store) => store,
+        Err(e) => return e.into_response(),
+    };
+    let found = match CacheService::find_by_nota(&state, &store, &no_nota).await {
+        Ok(found) => found,
+        Err(e) => return e.into_response(),
+    };
+    if found.is_empty() {
+        return DebugAppError::NotFound(format!("Nota {} not found in cache", no_nota)).into_response();
     }
 
-    // If all cached, return immediately
-    if missing_dates.is_empty() {
-        state.set_unauthorized(false).await;
-        let response = CachedDataResponse {
-            status: "completed".to_string(),
-            job_id: AppState::generate_job_id(&payload),
-            data: TransaksiResponse {
-                total_transaksi: cached_data.len(),
-                data: cached_data,
-            },
-            message: Some("All data from cache".to_string()),
+    let response = (StatusCode::OK, Json(TransaksiResponse::new(found))).into_response();
+    EtagService::respond(&headers, response).await
+}
+
+/// Query read API `/transactions*`: store, range, filter dan opsi export dalam satu request
+struct ReadRangeRequest {
+    store: StoreQuery,
+    export_query: ExportQuery,
+    filter: TransactionFilter,
+    from: String,
+    to: String,
+}
+
+async fn read_range(state: &AppState, parts: &Parts, request: ReadRangeRequest) -> Response {
+    state.cleanup_old_jobs().await;
+    let store = match state.config().resolve_store(request.store.store_id.as_deref()) {
+        Ok(store) => store,
+        Err(e) => return e.into_response(),
+    };
+
+    if let Some(mode) = StreamService::requested_mode(&parts.headers, &request.export_query) {
+        let row_filter = match export::stream_filter(&request.export_query, &request.filter) {
+            Ok(row_filter) => row_filter,
+            Err(e) => return e.into_response(),
+        };
+        let fill = AuthService::with_server_session(state, &store, &request.from, &request.to, |payload| {
+            let (state, store) = (state.clone(), store.clone());
+            async move { CacheService::fill_missing_dates(&state, &store, &payload).await }
+        }).await;
+        return match fill {
+            Ok(fill) => export::stream_response(state, fill, row_filter, mode),
+            Err(e) => e.into_response(),
         };
-        return (StatusCode::OK, Json(serde_json::to_value(response).unwrap()));
     }
 
-    info!("[SMART_FETCH] Missing {} dates, fetching...", missing_dates.len());
+    let result = AuthService::with_server_session(state, &store, &request.from, &request.to, |payload| {
+        let (state, store) = (state.clone(), store.clone());
+        async move { CacheService::get_range_with_smart_fetch(&state, &store, &payload).await }
+    }).await;
+    let payload = Payload { from: request.from, to: request.to, cookie: String::new() };
+    match result {
+        Ok(result) => cached_data_response(state, parts, &request.export_query, &request.filter, &payload, result).await,
+        Err(e) => e.into_response(),
+    }
+}
 
-    // Group consecutive missing dates into ranges
-    let mut ranges: Vec<(String, String)> = Vec::new();
-    let mut range_start = missing_dates[0].clone();
-    let mut range_end = missing_dates[0].clone();
+/// Dedup, filter/paging, render sesuai format yang diminta lalu ETag dari body
+async fn cached_data_response(
+    state: &AppState,
+    parts: &Parts,
+    export_query: &ExportQuery,
+    filter: &TransactionFilter,
+    payload: &Payload,
+    result: SmartFetchResult,
+) -> Response {
+    // Dedup dulu supaya filter & paging tidak menghitung nota yang sama dua kali
+    let (data, duplicates_collapsed) = Transaksi::dedup(result.data);
+    let (data, page) = if filter.is_empty() {
+        (data, None)
+    } else {
+        match FilterService::apply(data, filter) {
+            Ok(filtered) => (filtered.data, Some(filtered.page)),
+            Err(e) => return e.into_response(),
+        }
+    };
 
-    for i in 1..missing_dates.len() {
-        let prev_date = DateService::parse_date(&missing_dates[i - 1]).unwrap();
-        let curr_date = DateService::parse_date(&missing_dates[i]).unwrap();
-        
-        if (curr_date - prev_date).num_days() == 1 {
-            range_end = missing_dates[i].clone();
-        } else {
-            ranges.push((range_start.clone(), range_end.clone()));
-            range_start = missing_dates[i].clone();
-            range_end = missing_dates[i].clone();
+    // Accept: text/csv atau ?format=csv|xlsx|parquet -> kirim file, bukan JSON
+    let job_id = AppState::generate_job_id(payload);
+    let response = match ExportService::requested_format(&parts.headers, export_query) {
+        ExportFormat::Csv => export::csv_response(export_query, &state.config().export, payload, data),
+        ExportFormat::Xlsx => export::xlsx_response(payload, data),
+        ExportFormat::Parquet => export::parquet_response(payload, data),
+        ExportFormat::Json => {
+            let mut data = TransaksiResponse::new(data);
+            data.duplicates_collapsed = duplicates_collapsed;
+            let response = CachedDataResponse {
+                status: "completed".to_string(),
+                job_id,
+                data,
+                message: Some(result.message),
+                page,
+            };
+            (StatusCode::OK, Json(response)).into_response()
         }
+    };
+    // Dashboard yang polling (GET maupun POST /data-cached) cukup dapat 304 selama body tidak berubah
+    EtagService::respond(&parts.headers, response).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::models::test_support::transaksi;
+    use crate::models::DailyTransactions;
+    use axum::http::{header, HeaderValue, Request};
+
+    async fn cached_state() -> AppState {
+        let mut config = Config::default();
+        config.upstream.store_id = "264".to_string();
+        let state = AppState::new(config);
+        let (daily, _) = DailyTransactions::from_vec(vec![transaksi("01/10/2025", "INV-1", 12500)]);
+        state.cache.write().await.insert("264", "01/10/2025".to_string(), daily);
+        state
     }
-    ranges.push((range_start, range_end));
 
-    let ranges_count = ranges.len();
-    info!("[SMART_FETCH] Grouped into {} ranges", ranges_count);
+    fn etag_of(response: &Response) -> HeaderValue {
+        assert_eq!(response.status(), StatusCode::OK);
+        response.headers()[header::ETAG].clone()
+    }
 
-    // Fetch each range with 2-step pagination
-    for (from, to) in ranges {
-        let range_payload = Payload {
-            from: from.clone(),
-            to: to.clone(),
-            cookie: payload.cookie.clone(),
-        };
+    #[tokio::test]
+    async fn by_nota_answers_not_modified_for_the_previous_etag() {
+        let state = cached_state().await;
+        let nota = || Path("INV-1".to_string());
+        let first = get_transaction_by_nota(State(state.clone()), HeaderMap::new(), nota(), Query(StoreQuery::default())).await;
+        let etag = etag_of(&first);
 
-        match TransactionService::fetch_direct_two_loops(&range_payload).await {
-            Ok(response) => {
-                // Only cache if data is not empty
-                if !response.data.is_empty() {
-                    // Debug: Log sample transaction date format
-                    if let Some(first_tx) = response.data.first() {
-                        info!("[DEBUG] Sample transaction date from API: '{}'", first_tx.tanggal_transaksi);
-                    }
-                    
-                    let range_dates = DateService::get_date_range(&from, &to).unwrap();
-                    info!("[DEBUG] Expected cache key format (first 3): {:?}", range_dates.iter().take(3).collect::<Vec<_>>());
-                    
-                    let mut cache = state.cache.write().await;
-                    let mut total_cached = 0;
-                    for date in range_dates {
-                        let date_data: Vec<_> = response.data.iter()
-                            .filter(|t| {
-                                // Normalize transaction date to DD/MM/YYYY for comparison
-                                if let Ok(normalized) = DateService::normalize_date_for_api(&t.tanggal_transaksi) {
-                                    normalized == date
-                                } else {
-                                    false
-                                }
-                            })
-                            .cloned()
-                            .collect();
-                        if !date_data.is_empty() {
-                            info!("[CACHE_INSERT] Inserting {} transactions for key '{}'", date_data.len(), date);
-                            cache.insert(date, date_data);
-                            total_cached += 1;
-                        }
-                    }
-                    info!("[CACHE_SUMMARY] Cached {} dates out of {} fetched transactions", total_cached, response.data.len());
-                    drop(cache);
-                    // Save to file after caching
-                    if let Err(e) = CacheService::save_cache_to_file(&state).await {
-                        error!("[SMART_FETCH] Failed to save cache to file: {:?}", e);
-                    }
-                }
-                cached_data.extend(response.data);
-            }
-            Err(e) => {
-                if matches!(e, DebugAppError::Unauthorized(_)) {
-                    state.set_unauthorized(true).await;
-                    return (StatusCode::UNAUTHORIZED, Json(json!({
-                        "status": "unauthorized",
-                        "message": "Session expired or invalid cookie"
-                    })));
-                }
-                error!("[SMART_FETCH] Failed to fetch range {}-{}: {:?}", from, to, e);
-            }
-        }
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, etag.clone());
+        let second = get_transaction_by_nota(State(state), headers, nota(), Query(StoreQuery::default())).await;
+        assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(second.headers()[header::ETAG], etag);
     }
 
-    state.set_unauthorized(false).await;
-    let response = CachedDataResponse {
-        status: "completed".to_string(),
-        job_id: AppState::generate_job_id(&payload),
-        data: TransaksiResponse {
-            total_transaksi: cached_data.len(),
-            data: cached_data,
-        },
-        message: Some(format!("Fetched {} missing ranges", ranges_count)),
-    };
-    (StatusCode::OK, Json(serde_json::to_value(response).unwrap()))
-}
\ No newline at end of file
+    #[tokio::test]
+    async fn post_data_cached_answers_not_modified_for_the_previous_etag() {
+        let state = cached_state().await;
+        let request = |if_none_match: Option<&HeaderValue>| {
+            let mut builder = Request::builder().method("POST").uri("/data-cached");
+            if let Some(etag) = if_none_match {
+                builder = builder.header(header::IF_NONE_MATCH, etag);
+            }
+            let payload = Payload { from: "01/10/2025".to_string(), to: "01/10/2025".to_string(), cookie: "secret".to_string() };
+            let parts = builder.body(()).unwrap().into_parts().0;
+            get_cached_data(
+                State(state.clone()),
+                parts,
+                Query(StoreQuery::default()),
+                Query(ExportQuery::default()),
+                Query(TransactionFilter::default()),
+                Json(payload),
+            )
+        };
+        let etag = etag_of(&request(None).await);
+        assert_eq!(request(Some(&etag)).await.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(request(Some(&HeaderValue::from_static("\"other\""))).await.status(), StatusCode::OK);
+    }
+}
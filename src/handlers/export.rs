@@ -0,0 +1,227 @@
+use axum::{http::{header, HeaderMap, StatusCode}, response::{IntoResponse, Response}, Json, extract::{Query, State}};
+use std::path::PathBuf;
+use crate::config::ExportConfig;
+use crate::errors::{DebugAppError, ErrorResponse};
+use crate::models::{ExportFormat, ExportQuery, ParquetExportRequest, ParquetExportResponse, Payload, StoreQuery, StreamMode, Transaksi, TransactionFilter, TransaksiResponse};
+use crate::services::{CacheService, DateService, EtagService, ExportService, FilterService, RowFilter, SmartFill, StreamService, XLSX_CONTENT_TYPE};
+use crate::state::AppState;
+use tracing::{info, error, Span};
+
+/// Export data range (smart fetch seperti `/data-cached`), default CSV
+#[utoipa::path(
+    post,
+    path = "/export",
+    tag = "export",
+    params(StoreQuery, ExportQuery),
+    request_body = Payload,
+    responses(
+        (status = 200, description = "CSV (default), XLSX, Parquet, JSON or a stream (`?stream=`)", content(
+            (String = "text/csv"),
+            (Vec<u8> = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+            (Vec<u8> = "application/vnd.apache.parquet"),
+            (TransaksiResponse = "application/json"),
+            (String = "application/x-ndjson"),
+        )),
+        (status = 304, description = "`If-None-Match` matches the current ETag"),
+        (status = 400, description = "Invalid date, export option or unknown store", body = ErrorResponse),
+        (status = 401, description = "Session expired or invalid cookie", body = ErrorResponse),
+        (status = 500, description = "Export failed", body = ErrorResponse),
+        (status = 503, description = "`MAX_CONCURRENT_JOBS` upstream fetches already running", body = ErrorResponse),
+        (status = 504, description = "Upstream fetch exceeded `JOB_TIMEOUT_SECS`", body = ErrorResponse),
+    )
+)]
+pub async fn export_data(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(store): Query<StoreQuery>,
+    Query(export_query): Query<ExportQuery>,
+    Json(payload): Json<Payload>,
+) -> Response {
+    let store = match state.config().resolve_store(store.store_id.as_deref()) {
+        Ok(store) => store,
+        Err(e) => return e.into_response(),
+    };
+    if let Some(mode) = StreamService::requested_mode(&headers, &export_query) {
+        let row_filter = match stream_filter(&export_query, &TransactionFilter::default()) {
+            Ok(row_filter) => row_filter,
+            Err(e) => return e.into_response(),
+        };
+        return match CacheService::fill_missing_dates(&state, &store, &payload).await {
+            Ok(fill) => stream_response(&state, fill, row_filter, mode),
+            Err(e) => e.into_response(),
+        };
+    }
+
+    let result = match CacheService::get_range_with_smart_fetch(&state, &store, &payload).await {
+        Ok(result) => result,
+        Err(e) => return e.into_response(),
+    };
+
+    // Tanpa ?format=, JSON hanya kalau client minta `Accept: application/json`
+    let accepts_json = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("application/json"));
+    let format = export_query.format.unwrap_or(if accepts_json { ExportFormat::Json } else { ExportFormat::Csv });
+
+    let response = match format {
+        ExportFormat::Csv => csv_response(&export_query, &state.config().export, &payload, result.data),
+        ExportFormat::Xlsx => xlsx_response(&payload, result.data),
+        ExportFormat::Parquet => parquet_response(&payload, result.data),
+        ExportFormat::Json => (StatusCode::OK, Json(TransaksiResponse::new(result.data))).into_response(),
+    };
+    EtagService::respond(&headers, response).await
+}
+
+/// Validasi opsi streaming: hanya JSON, dan hanya filter per baris
+pub(crate) fn stream_filter(export_query: &ExportQuery, filter: &TransactionFilter) -> Result<RowFilter, DebugAppError> {
+    if export_query.format.is_some_and(|f| f != ExportFormat::Json) {
+        return Err(DebugAppError::InvalidInput("stream is only available for JSON output".to_string()));
+    }
+    FilterService::row_filter(filter)
+}
+
+/// Kirim isi cache per tanggal sebagai stream (cache sudah dilengkapi lewat smart fetch)
+pub(crate) fn stream_response(state: &AppState, fill: SmartFill, row_filter: RowFilter, mode: StreamMode) -> Response {
+    info!("[STREAM] Streaming {} dates as {:?} ({})", fill.dates.len(), mode, fill.message);
+    let body = StreamService::cache_body(state.clone(), fill.store, fill.dates, fill.uncached, row_filter, mode);
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, StreamService::content_type(mode))],
+        body,
+    ).into_response()
+}
+
+pub(crate) fn csv_response(export_query: &ExportQuery, defaults: &ExportConfig, payload: &Payload, data: Vec<Transaksi>) -> Response {
+    let options = match ExportService::csv_options(export_query, defaults) {
+        Ok(options) => options,
+        Err(e) => return e.into_response(),
+    };
+
+    let (data, _) = Transaksi::dedup(data);
+    let filename = ExportService::filename(payload, "csv");
+    info!("[EXPORT] CSV {} rows -> {} (streamed)", data.len(), filename);
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        ExportService::csv_body(data, options),
+    ).into_response()
+}
+
+pub(crate) fn xlsx_response(payload: &Payload, data: Vec<Transaksi>) -> Response {
+    let (data, _) = Transaksi::dedup(data);
+    file_response(
+        "XLSX",
+        ExportService::to_xlsx(&data),
+        ExportService::filename(payload, "xlsx"),
+        XLSX_CONTENT_TYPE,
+        data.len(),
+    )
+}
+
+pub(crate) fn parquet_response(payload: &Payload, data: Vec<Transaksi>) -> Response {
+    let (data, _) = Transaksi::dedup(data);
+    file_response(
+        "Parquet",
+        ExportService::to_parquet(&data),
+        ExportService::filename(payload, "parquet"),
+        "application/vnd.apache.parquet",
+        data.len(),
+    )
+}
+
+fn file_response(
+    label: &str,
+    content: Result<Vec<u8>, DebugAppError>,
+    filename: String,
+    content_type: &str,
+    rows: usize,
+) -> Response {
+    match content {
+        Ok(bytes) => {
+            info!("[EXPORT] {} {} rows -> {}", label, rows, filename);
+            (
+                StatusCode::OK,
+                [
+                    (header::CONTENT_TYPE, content_type.to_string()),
+                    (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+                ],
+                bytes,
+            ).into_response()
+        }
+        Err(e) => {
+            error!("[EXPORT] Failed to build {}: {:?}", label, e);
+            e.into_response()
+        }
+    }
+}
+
+/// Tulis cache (range diperlebar ke bulan penuh, atau seluruhnya) ke `PARQUET_EXPORT_DIR`, dipartisi per bulan.
+/// Store selain `STORE_ID` ditulis ke subfolder `store-<id>` supaya partisinya tidak saling timpa.
+#[utoipa::path(
+    post,
+    path = "/export/parquet",
+    tag = "export",
+    params(StoreQuery),
+    request_body = ParquetExportRequest,
+    responses(
+        (status = 200, description = "Partitions written", body = ParquetExportResponse),
+        (status = 400, description = "Invalid or incomplete range, or unknown store", body = ErrorResponse),
+        (status = 500, description = "Write failed", body = ErrorResponse),
+    )
+)]
+pub async fn export_parquet_partitions(
+    State(state): State<AppState>,
+    Query(store): Query<StoreQuery>,
+    Json(request): Json<ParquetExportRequest>,
+) -> Result<Json<ParquetExportResponse>, DebugAppError> {
+    let config = state.config();
+    let store = config.resolve_store(store.store_id.as_deref())?;
+    // File partisi per bulan ditulis ulang utuh, jadi range selalu diperlebar ke bulan penuh
+    let (from, to) = match (request.from.as_deref(), request.to.as_deref()) {
+        (Some(from), Some(to)) => {
+            let (from, to) = DateService::whole_months(from, to)
+                .map_err(|_| DebugAppError::DateParse("Invalid date format".to_string()))?;
+            info!("[EXPORT] Parquet range widened to whole months: {} - {}", from, to);
+            (Some(from), Some(to))
+        }
+        (from, to) => (from.map(str::to_string), to.map(str::to_string)),
+    };
+    let transactions = CacheService::collect_cached(&state, &store, from.as_deref(), to.as_deref()).await?;
+    let (transactions, _) = Transaksi::dedup(transactions);
+
+    let mut export_dir = PathBuf::from(&config.export.parquet_export_dir);
+    if !config.is_default_store(&store) {
+        export_dir.push(format!("store-{}", store));
+    }
+    let dir = export_dir.clone();
+    // Span request ikut ke thread blocking supaya log-nya tetap punya request_id
+    let span = Span::current();
+    let result = tokio::task::spawn_blocking(move || {
+        span.in_scope(|| ExportService::write_parquet_partitions(&dir, &transactions))
+    }).await;
+
+    match result {
+        Ok(Ok(files)) => {
+            let total_rows: usize = files.iter().map(|f| f.rows).sum();
+            info!("[EXPORT] Parquet {} rows -> {} partitions in '{}'", total_rows, files.len(), export_dir.display());
+            Ok(Json(ParquetExportResponse {
+                status: "completed".to_string(),
+                directory: export_dir.display().to_string(),
+                total_rows,
+                files,
+            }))
+        }
+        Ok(Err(e)) => {
+            error!("[EXPORT] Failed to write Parquet partitions: {:?}", e);
+            Err(e)
+        }
+        Err(e) => {
+            error!("[EXPORT] Parquet task panicked: {:?}", e);
+            Err(DebugAppError::FileWrite(e.to_string()))
+        }
+    }
+}
@@ -1,19 +1,58 @@
-#![allow(dead_code)]
-#![allow(unused_imports)]
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use crate::models::{Health, ReadinessResponse};
+use crate::openapi::ApiDoc;
+use crate::services::HealthService;
+use crate::state::AppState;
+use utoipa::OpenApi;
 
-use axum::{http::StatusCode, response::IntoResponse, Json};
-use serde::Deserialize;
-use crate::models::{Health, EchoResponse};
-
-#[derive(Deserialize)]
-pub struct EchoPayload {
-    pub message: String,
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "health",
+    responses((status = 200, description = "Service is up", body = Health))
+)]
+pub async fn root() -> impl IntoResponse {
+    Json(Health { status: "ok" })
 }
 
-pub async fn root() -> impl IntoResponse {
+/// Liveness: proses hidup dan bisa melayani HTTP, tanpa cek dependency
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "health",
+    responses((status = 200, description = "Process is alive", body = Health))
+)]
+pub async fn health_live() -> impl IntoResponse {
     Json(Health { status: "ok" })
 }
 
-pub async fn echo(Json(payload): Json<EchoPayload>) -> impl IntoResponse {
-    (StatusCode::CREATED, Json(EchoResponse { echoed: payload.message }))
-}
\ No newline at end of file
+/// Readiness: status cache, upstream, job dan persistence. `503` kalau degraded.
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "Ready", body = ReadinessResponse),
+        (status = 503, description = "Degraded, see `problems`", body = ReadinessResponse),
+    )
+)]
+pub async fn health_ready(State(state): State<AppState>) -> impl IntoResponse {
+    let readiness = HealthService::readiness(&state).await;
+    let status = if readiness.problems.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(readiness))
+}
+
+/// Dokumen OpenAPI 3 (semua route dan model) untuk generate typed client
+#[utoipa::path(
+    get,
+    path = "/openapi.json",
+    tag = "health",
+    responses((status = 200, description = "OpenAPI 3 document", content_type = "application/json"))
+)]
+pub async fn get_openapi() -> impl IntoResponse {
+    Json(ApiDoc::openapi())
+}
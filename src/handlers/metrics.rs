@@ -0,0 +1,47 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use crate::services::MetricsService;
+use crate::state::AppState;
+use tokio::time::Instant;
+use tracing::Span;
+
+/// Metrics format teks Prometheus untuk di-scrape
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "health",
+    responses((status = 200, description = "Prometheus text exposition format", content_type = "text/plain", body = String))
+)]
+pub async fn get_metrics(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        MetricsService::render(&state).await,
+    )
+}
+
+/// Middleware: hitung request dan latency per route template (`/transactions/{date}`, bukan path asli),
+/// sekaligus memberi nama span request untuk trace
+pub async fn track_http_metrics(request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    // Nama span trace per route supaya `/transactions/{date}` tidak pecah per tanggal
+    let span = Span::current();
+    span.record("otel.name", format!("{} {}", method, route));
+    span.record("http.route", route.as_str());
+
+    let response = next.run(request).await;
+    let status = response.status().as_u16();
+    span.record("http.response.status_code", status);
+    MetricsService::observe_http(&method, &route, status, started.elapsed());
+    response
+}
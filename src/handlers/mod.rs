@@ -1,9 +1,19 @@
 pub mod health;
-pub mod transaction;
 pub mod cache;
 pub mod auth;
+pub mod audit;
+pub mod export;
+pub mod report;
+pub mod metrics;
+pub mod request_span;
+pub mod admin;
 
 pub use health::*;
-pub use transaction::*;
 pub use cache::*;
-pub use auth::*;
\ No newline at end of file
+pub use auth::*;
+pub use audit::*;
+pub use export::*;
+pub use report::*;
+pub use metrics::*;
+pub use request_span::*;
+pub use admin::*;
\ No newline at end of file
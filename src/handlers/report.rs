@@ -0,0 +1,104 @@
+use axum::{http::{header, HeaderMap, StatusCode}, response::{IntoResponse, Response}, extract::{Query, State}, Json};
+use crate::errors::{DebugAppError, ErrorResponse};
+use crate::models::{ClosingReportQuery, RangeQuery, StoreQuery, StoreSummaryResponse, Transaksi};
+use crate::services::{CacheService, DateService, EtagService, ReportService};
+use crate::state::AppState;
+use tracing::{info, error};
+
+/// PDF laporan penutupan harian, hanya dari cache (tidak request ke upstream)
+#[utoipa::path(
+    get,
+    path = "/report/closing",
+    tag = "report",
+    params(ClosingReportQuery, StoreQuery),
+    responses(
+        (status = 200, description = "Printable PDF", content_type = "application/pdf", body = Vec<u8>),
+        (status = 304, description = "`If-None-Match` matches the current ETag"),
+        (status = 400, description = "Invalid date or unknown store", body = ErrorResponse),
+        (status = 404, description = "Date is not cached yet", body = ErrorResponse),
+        (status = 500, description = "Template or rendering failure", body = ErrorResponse),
+    )
+)]
+pub async fn get_closing_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ClosingReportQuery>,
+    Query(store): Query<StoreQuery>,
+) -> Response {
+    let config = state.config();
+    let store_id = match config.resolve_store(store.store_id.as_deref()) {
+        Ok(store_id) => store_id,
+        Err(e) => return e.into_response(),
+    };
+    let date = match DateService::parse_date(&query.date) {
+        Ok(d) => d,
+        Err(_) => return DebugAppError::DateParse("Invalid date format".to_string()).into_response(),
+    };
+    let cache_key = date.format("%d/%m/%Y").to_string();
+
+    let Some(transactions) = CacheService::get_from_memory_cache(&state, &store_id, &cache_key).await else {
+        return DebugAppError::NotFound(format!("No cached transactions for {}, fetch the date first", cache_key))
+            .into_response();
+    };
+
+    let template = match ReportService::load_pdf_template(config.export.pdf_template_path.as_deref()).await {
+        Ok(t) => t,
+        Err(e) => {
+            error!("[REPORT] Failed to load PDF template: {:?}", e);
+            return e.into_response();
+        }
+    };
+
+    let store_id = store_id.as_str();
+    let (transactions, _) = Transaksi::dedup(transactions);
+
+    match ReportService::render_closing_pdf(date, store_id, &transactions, &template) {
+        Ok(pdf) => {
+            let filename = format!("closing_{}_{}.pdf", store_id, date.format("%Y-%m-%d"));
+            info!("[REPORT] Closing report {} ({} transaksi)", filename, transactions.len());
+            let response = (
+                StatusCode::OK,
+                [
+                    (header::CONTENT_TYPE, "application/pdf".to_string()),
+                    (header::CONTENT_DISPOSITION, format!("inline; filename=\"{}\"", filename)),
+                ],
+                pdf,
+            ).into_response();
+            // Baris "Dicetak" berisi waktu render, jadi PDF selalu dapat ETag baru (tidak pernah 304)
+            EtagService::respond(&headers, response).await
+        }
+        Err(e) => {
+            error!("[REPORT] Failed to render PDF: {:?}", e);
+            e.into_response()
+        }
+    }
+}
+
+/// Ringkasan lintas store (jumlah tanggal, transaksi, total) dari cache saja, tanpa request ke upstream
+#[utoipa::path(
+    get,
+    path = "/report/stores",
+    tag = "report",
+    params(RangeQuery),
+    responses(
+        (status = 200, description = "Totals per configured store and overall", body = StoreSummaryResponse),
+        (status = 400, description = "Invalid or incomplete from/to", body = ErrorResponse),
+        (status = 500, description = "A date spilled to disk could not be read", body = ErrorResponse),
+    )
+)]
+pub async fn get_store_summary(
+    State(state): State<AppState>,
+    Query(query): Query<RangeQuery>,
+) -> Result<Json<StoreSummaryResponse>, DebugAppError> {
+    let stores = ReportService::store_summaries(&state, query.from.as_deref(), query.to.as_deref()).await?;
+    info!("[REPORT] Store summary for {} stores", stores.len());
+
+    Ok(Json(StoreSummaryResponse {
+        status: "ok".to_string(),
+        from: query.from,
+        to: query.to,
+        total_transactions: stores.iter().map(|s| s.transactions).sum(),
+        total_tagihan: stores.iter().map(|s| s.total_tagihan).sum(),
+        stores,
+    }))
+}
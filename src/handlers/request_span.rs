@@ -0,0 +1,27 @@
+use axum::http::Request;
+use tracing::{field, info_span, Span};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Span per request untuk `TraceLayer`. `request_id` diambil dari `X-Request-Id` (dari client,
+/// atau dibuat oleh `SetRequestIdLayer`), jadi semua event service dan upstream di bawahnya
+/// bisa dikaitkan ke request yang memicunya. `otel.name` dan `http.route` diisi oleh
+/// `track_http_metrics` setelah routing (route template, bukan path asli).
+pub fn make_request_span<B>(request: &Request<B>) -> Span {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("-");
+
+    info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        uri = %request.uri(),
+        otel.name = field::Empty,
+        otel.kind = "server",
+        http.route = field::Empty,
+        http.response.status_code = field::Empty,
+    )
+}
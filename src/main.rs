@@ -1,58 +1,159 @@
-use axum::{routing::{get, post}, Router};
-use tower_http::cors::CorsLayer;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use std::env;
+use axum::{extract::DefaultBodyLimit, middleware, routing::{get, post}, Router};
+use axum::http::HeaderName;
+use tower::ServiceBuilder;
+use tower_http::{
+    compression::CompressionLayer,
+    cors::CorsLayer,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::{DefaultOnResponse, TraceLayer},
+};
+use tracing::Level;
 
+mod cli;
+mod config;
 mod errors;
 mod handlers;
 mod models;
+mod openapi;
 mod services;
 mod state;
+mod telemetry;
 
+use config::Config;
 use handlers::*;
 use state::AppState;
+use telemetry::Telemetry;
 
 #[tokio::main]
 async fn main() {
     // Load environment variables
     dotenv::dotenv().ok();
+
+    // Subcommand CLI (`cache export` / `cache import`); tanpa argumen jalankan server
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match cli::Command::parse(&args) {
+        Ok(cli::Command::Help) => {
+            print!("{}", cli::USAGE);
+            return;
+        }
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    // Load + validasi config; gagal di sini lebih baik daripada request pertama ke example.com
+    let mut config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprint!("{}", e);
+            std::process::exit(1);
+        }
+    };
     
-    // Setup logging with env variable
-    let log_level = env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string());
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| log_level.into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // `cache import` menulis ke file cache: jangan lanjut dari cache yang gagal di-load (file dipindah)
+    if matches!(command, cli::Command::CacheImport { .. }) {
+        config.cache.strict_load = true;
+    }
 
-    let state = AppState::new();
+    // Logging (text/JSON) + ekspor trace OTLP kalau endpoint di-set
+    let telemetry = Telemetry::init(&config);
+
+    for line in config.to_string().lines() {
+        tracing::info!("[CONFIG] {}", line);
+    }
+
+    let bind_addr = format!("{}:{}", config.server.host, config.server.port);
+    let state = AppState::new(config);
     
     // Load cache dari file backup saat startup
-    if let Err(e) = state.load_cache_from_file().await {
-        tracing::warn!("Failed to load cache from file: {:?}", e);
+    // Hanya gagal kalau CACHE_STRICT_LOAD aktif (selalu untuk CLI) dan file cache tidak bisa di-load.
+    // `cache export` hanya membaca: tidak ada file yang dipindah, ditulis ulang atau di-spill.
+    let loaded = match command {
+        cli::Command::CacheExport { .. } => state.load_cache_read_only().await,
+        _ => state.load_cache_from_file().await.map_err(|e| e.to_string()),
+    };
+    if let Err(e) = loaded {
+        if command == cli::Command::Serve {
+            tracing::error!("Refusing to start, cache file could not be loaded (CACHE_STRICT_LOAD): {}", e);
+        } else {
+            eprintln!("Cache file could not be loaded, fix or move it first: {}", e);
+        }
+        telemetry.shutdown();
+        std::process::exit(1);
+    }
+
+    if command != cli::Command::Serve {
+        let code = cli::run(command, &state).await;
+        telemetry.shutdown();
+        std::process::exit(code);
     }
 
+    // Hot-reload config (SIGHUP / file config berubah)
+    services::ConfigService::spawn_watcher(state.clone(), telemetry.log_handle.clone());
+
+    // Operasi admin, semua butuh `Authorization: Bearer <ADMIN_TOKEN>`
+    let admin = Router::new()
+        .route("/admin/cache", get(admin_list_cache).delete(admin_evict_cache))
+        .route("/admin/cache/clean-empty", post(admin_clean_empty_cache))
+        .route("/admin/cache/flush", post(admin_flush_cache))
+        .route("/admin/processing/clear", post(admin_clear_processing))
+        .route("/admin/cache/export", get(admin_export_cache))
+        .route(
+            "/admin/cache/import",
+            post(admin_import_cache).layer(DefaultBodyLimit::max(services::MAX_ARCHIVE_BYTES)),
+        )
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_admin_token));
+
     let app = Router::new()
         .route("/", get(root))
+        .route("/health/live", get(health_live))
+        .route("/health/ready", get(health_ready))
+        .route("/openapi.json", get(get_openapi))
         .route("/data-cached", post(get_cached_data))
+        .route("/transactions", get(get_transactions))
+        .route("/transactions/by-nota/{no_nota}", get(get_transaction_by_nota))
+        .route("/transactions/{date}", get(get_transactions_by_date))
+        .route("/export", post(export_data))
+        .route("/export/parquet", post(export_parquet_partitions))
+        .route("/report/closing", get(get_closing_report))
+        .route("/report/stores", get(get_store_summary))
         .route("/force-refresh", post(force_refresh_data))
         .route("/login", get(get_login_status).post(post_login))
-        .with_state(state)
+        .route("/audit/changes", get(get_audit_changes))
+        .route("/metrics", get(get_metrics))
+        .merge(admin)
+        // Request count & latency per route template
+        .layer(middleware::from_fn(track_http_metrics))
+        .with_state(state.clone())
+        // X-Request-Id: pakai dari client atau buat UUID baru, jadi field span, lalu dikembalikan di response
+        .layer(
+            ServiceBuilder::new()
+                .layer(SetRequestIdLayer::new(HeaderName::from_static(REQUEST_ID_HEADER), MakeRequestUuid))
+                .layer(
+                    TraceLayer::new_for_http()
+                        .make_span_with(make_request_span)
+                        .on_response(DefaultOnResponse::new().level(Level::INFO)),
+                )
+                .layer(PropagateRequestIdLayer::new(HeaderName::from_static(REQUEST_ID_HEADER))),
+        )
+        // gzip/brotli sesuai Accept-Encoding client
+        .layer(CompressionLayer::new())
         .layer(
             CorsLayer::new()
                 .allow_origin(tower_http::cors::Any)
                 .allow_methods(tower_http::cors::Any)
                 .allow_headers(tower_http::cors::Any)
+                .expose_headers([HeaderName::from_static(REQUEST_ID_HEADER)])
         );
 
-    // Get host and port from environment variables
-    let host = env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
-    let port = env::var("PORT").unwrap_or_else(|_| "3000".to_string());
-    let bind_addr = format!("{}:{}", host, port);
-    
     let listener = tokio::net::TcpListener::bind(&bind_addr).await.unwrap();
     tracing::info!("listening on {}", listener.local_addr().unwrap());
-    axum::serve(listener, app).await.unwrap();
+    let server = axum::serve(listener, app)
+        .with_graceful_shutdown(services::ShutdownService::wait_for_signal(state.clone()));
+    services::ShutdownService::run(&state, server).await;
+
+    // Kirim span terakhir setelah save cache, supaya span shutdown ikut terekspor
+    telemetry.shutdown();
 }
\ No newline at end of file
@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// Versi format archive cache; naikkan kalau isi `manifest.json` / `cache.json` berubah tidak kompatibel
+pub const CACHE_ARCHIVE_SCHEMA_VERSION: u32 = 1;
+
+/// `manifest.json` di dalam archive `.tar.gz`: versi schema, store, cakupan tanggal dan checksum
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct ArchiveManifest {
+    pub schema_version: u32,
+    /// Versi aplikasi yang membuat archive
+    pub app_version: String,
+    pub created_at: DateTime<Utc>,
+    pub store_id: String,
+    /// Range yang diminta saat export (`None` = seluruh cache)
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Tanggal pertama dan terakhir yang benar-benar ada di archive
+    pub first_date: Option<String>,
+    pub last_date: Option<String>,
+    pub total_dates: usize,
+    pub total_transactions: usize,
+    /// SHA-256 (hex) dari `cache.json` persis seperti di archive
+    pub data_sha256: String,
+    pub dates: Vec<ArchiveDateEntry>,
+}
+
+/// Checksum per tanggal, dihitung dari JSON array transaksi tanggal itu
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct ArchiveDateEntry {
+    pub date: String,
+    pub transactions: usize,
+    pub sha256: String,
+}
+
+/// Cara import memperlakukan tanggal yang sudah ada di cache
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportPolicy {
+    /// Merge per `no_nota` (sama seperti re-fetch): nota dari archive meng-update yang ada
+    #[default]
+    Merge,
+    /// Ganti isi tanggal dengan isi archive
+    Overwrite,
+    /// Tanggal yang sudah ada di cache tidak disentuh
+    SkipExisting,
+}
+
+impl ImportPolicy {
+    /// Untuk CLI: `merge`, `overwrite`, `skip-existing` / `skip_existing`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim() {
+            "merge" => Some(ImportPolicy::Merge),
+            "overwrite" => Some(ImportPolicy::Overwrite),
+            "skip-existing" | "skip_existing" => Some(ImportPolicy::SkipExisting),
+            _ => None,
+        }
+    }
+}
+
+/// Query parameter untuk `POST /admin/cache/import?policy=merge`
+#[derive(Deserialize, IntoParams, Debug, Clone, Default)]
+#[into_params(parameter_in = Query)]
+pub struct ImportQuery {
+    pub policy: Option<ImportPolicy>,
+    /// Harus sama dengan store di manifest archive; kosong = store dari manifest
+    pub store_id: Option<String>,
+}
+
+/// Hasil import per kategori tanggal
+#[derive(Serialize, ToSchema, Debug, Clone, Default)]
+pub struct ImportSummary {
+    pub policy: ImportPolicy,
+    /// Tanggal baru yang belum ada di cache
+    pub added: usize,
+    pub merged: usize,
+    pub overwritten: usize,
+    pub skipped: usize,
+    pub transactions: usize,
+    pub duplicates_collapsed: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CacheImportResponse {
+    pub status: String,
+    pub manifest: ArchiveManifest,
+    pub summary: ImportSummary,
+}
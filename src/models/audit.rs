@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use super::Transaksi;
+
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    AmountChanged,
+    DescriptionChanged,
+}
+
+/// Satu perubahan yang terdeteksi saat force-refresh dibandingkan dengan cache lama
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct ChangeLogEntry {
+    pub detected_at: DateTime<Utc>,
+    /// Kosong di entry lama (sebelum multi-store) = store default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub store_id: Option<String>,
+    pub date: String,
+    pub no_nota: String,
+    pub kind: ChangeKind,
+    pub old: Option<Transaksi>,
+    pub new: Option<Transaksi>,
+}
+
+#[derive(Serialize, ToSchema, Debug, Clone, Copy, Default)]
+pub struct ChangeSummary {
+    pub added: usize,
+    pub removed: usize,
+    pub amount_changed: usize,
+    pub description_changed: usize,
+}
+
+impl ChangeSummary {
+    pub fn from_entries(entries: &[ChangeLogEntry]) -> Self {
+        let mut summary = Self::default();
+        for entry in entries {
+            match entry.kind {
+                ChangeKind::Added => summary.added += 1,
+                ChangeKind::Removed => summary.removed += 1,
+                ChangeKind::AmountChanged => summary.amount_changed += 1,
+                ChangeKind::DescriptionChanged => summary.description_changed += 1,
+            }
+        }
+        summary
+    }
+}
@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Versi format file backup cache (`CACHE_FILE_PATH`).
+/// v1: map `tanggal -> [Transaksi]` tanpa envelope (file lama). v2: envelope `CacheFile`.
+/// Naikkan dan tambah migrasi di `CacheFileService` setiap kali bentuk file atau `Transaksi` berubah.
+pub const CACHE_FILE_SCHEMA_VERSION: u32 = 2;
+
+/// Envelope file backup cache. `D` = map tanggal ke transaksi (`DailyTransactions` saat tulis,
+/// `Vec<Transaksi>` saat baca).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CacheFile<D> {
+    pub schema_version: u32,
+    /// Waktu file ini ditulis
+    pub created_at: DateTime<Utc>,
+    pub store_id: String,
+    pub dates: D,
+}
@@ -0,0 +1,469 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use super::{DailyTransactions, MergeStats, Transaksi};
+
+/// Status file spill untuk tanggal yang ada di memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpillState {
+    /// Tidak ada file spill
+    None,
+    /// File spill sama persis dengan data di memory; evict cukup dibuang dari memory
+    Current,
+    /// File spill lebih lama dari data di memory; dihapus setelah save file backup berikutnya berhasil
+    Stale,
+}
+
+#[derive(Debug)]
+struct HotEntry {
+    daily: DailyTransactions,
+    bytes: usize,
+    last_access: AtomicU64,
+    spill: SpillState,
+    /// Berubah setiap isi tanggal berubah; dipakai evict untuk tahu apakah data yang ditulis ke spill masih sama
+    version: u64,
+}
+
+/// Hasil `evict_if_unchanged` setelah file spill ditulis tanpa lock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvictOutcome {
+    /// Tanggal dipindah ke spill
+    Evicted,
+    /// Tanggal berubah sejak di-snapshot dan tetap di memory; file spill yang baru ditulis jadi stale
+    Changed,
+    /// Tanggal sudah dihapus sejak di-snapshot; file spill yang baru ditulis harus dibuang
+    Gone,
+}
+
+/// Data yang perlu ditulis sebelum tanggal di-evict (`CacheStore::spill_candidate`)
+#[derive(Debug)]
+pub struct SpillCandidate {
+    pub version: u64,
+    /// `None` kalau file spill sudah sama dengan data di memory
+    pub daily: Option<DailyTransactions>,
+}
+
+/// Tanggal yang hanya ada di file spill
+#[derive(Debug, Clone, Copy)]
+pub struct ColdEntry {
+    pub transactions: usize,
+}
+
+/// Tanggal milik satu store
+#[derive(Debug, Default)]
+struct StoreDates {
+    hot: HashMap<String, HotEntry>,
+    cold: HashMap<String, ColdEntry>,
+}
+
+/// Cache transaksi per store lalu per tanggal, dengan batas memory bersama untuk semua store.
+/// Tanggal "hot" ada di memory (dengan ukuran terukur dan urutan akses LRU), tanggal "cold" sudah
+/// dipindah ke file spill dan hanya dicatat jumlah transaksinya. Struktur ini tidak melakukan I/O;
+/// baca/tulis file spill ada di `CacheService`.
+#[derive(Debug, Default)]
+pub struct CacheStore {
+    stores: HashMap<String, StoreDates>,
+    hot_bytes: usize,
+    clock: AtomicU64,
+    evictions: u64,
+}
+
+impl CacheStore {
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    fn hot(&self, store: &str, date: &str) -> Option<&HotEntry> {
+        self.stores.get(store)?.hot.get(date)
+    }
+
+    fn cold(&self, store: &str, date: &str) -> Option<&ColdEntry> {
+        self.stores.get(store)?.cold.get(date)
+    }
+
+    /// Entry store dibuat saat pertama dipakai dan tidak pernah dihapus, jadi store yang semua
+    /// tanggalnya sudah dihapus tetap ikut ditulis (kosong) saat save
+    fn store_mut(&mut self, store: &str) -> &mut StoreDates {
+        self.stores.entry(store.to_string()).or_default()
+    }
+
+    /// Data tanggal yang ada di memory; menandai tanggal ini sebagai baru diakses
+    pub fn get(&self, store: &str, date: &str) -> Option<&DailyTransactions> {
+        let entry = self.hot(store, date)?;
+        entry.last_access.store(self.tick(), Ordering::Relaxed);
+        Some(&entry.daily)
+    }
+
+    /// Seperti `get` tapi tanpa mengubah urutan LRU (scan internal, tulis spill)
+    pub fn peek(&self, store: &str, date: &str) -> Option<&DailyTransactions> {
+        self.hot(store, date).map(|entry| &entry.daily)
+    }
+
+    /// Tanggal ada di cache (memory atau spill)
+    pub fn contains(&self, store: &str, date: &str) -> bool {
+        self.is_hot(store, date) || self.is_cold(store, date)
+    }
+
+    pub fn is_hot(&self, store: &str, date: &str) -> bool {
+        self.hot(store, date).is_some()
+    }
+
+    pub fn is_cold(&self, store: &str, date: &str) -> bool {
+        self.cold(store, date).is_some()
+    }
+
+    /// Jumlah transaksi tanggal ini tanpa me-load dari spill
+    pub fn transaction_count(&self, store: &str, date: &str) -> Option<usize> {
+        self.hot(store, date)
+            .map(|entry| entry.daily.len())
+            .or_else(|| self.cold(store, date).map(|entry| entry.transactions))
+    }
+
+    /// Merge ke tanggal yang ada di memory (atau tanggal baru). Tanggal cold harus di-load dulu,
+    /// kalau tidak data di spill tidak ikut ter-merge.
+    pub fn merge(&mut self, store: &str, date: &str, transactions: Vec<Transaksi>) -> MergeStats {
+        debug_assert!(!self.is_cold(store, date), "merge into cold date {} of store {}", date, store);
+        let tick = self.tick();
+        let entry = self.store_mut(store).hot.entry(date.to_string()).or_insert_with(|| HotEntry {
+            daily: DailyTransactions::default(),
+            bytes: 0,
+            last_access: AtomicU64::new(tick),
+            spill: SpillState::None,
+            version: tick,
+        });
+        let stats = entry.daily.merge(transactions);
+        entry.last_access.store(tick, Ordering::Relaxed);
+        if stats.added + stats.updated > 0 {
+            entry.version = tick;
+            if entry.spill == SpillState::Current {
+                entry.spill = SpillState::Stale;
+            }
+        }
+        let (old_bytes, bytes) = (entry.bytes, entry.daily.memory_size());
+        entry.bytes = bytes;
+        self.hot_bytes = self.hot_bytes - old_bytes + bytes;
+        stats
+    }
+
+    /// Ganti isi tanggal (force-refresh, import overwrite). File spill lama, kalau ada, jadi stale.
+    pub fn insert(&mut self, store: &str, date: String, daily: DailyTransactions) {
+        let dates = self.store_mut(store);
+        let had_spill = dates.cold.remove(&date).is_some()
+            || dates.hot.get(&date).is_some_and(|entry| entry.spill != SpillState::None);
+        let spill = if had_spill { SpillState::Stale } else { SpillState::None };
+        self.put_hot(store, date, daily, spill);
+    }
+
+    /// Pasang tanggal yang baru di-load dari spill. Diabaikan kalau tanggal sudah di memory
+    /// atau sudah dihapus sejak dibaca.
+    pub fn promote(&mut self, store: &str, date: &str, daily: DailyTransactions) -> bool {
+        if self.store_mut(store).cold.remove(date).is_none() {
+            return false;
+        }
+        self.put_hot(store, date.to_string(), daily, SpillState::Current);
+        true
+    }
+
+    /// Pasang tanggal dengan status spill tertentu (load saat startup)
+    pub fn insert_with_spill(&mut self, store: &str, date: String, daily: DailyTransactions, spill: SpillState) {
+        self.store_mut(store).cold.remove(&date);
+        self.put_hot(store, date, daily, spill);
+    }
+
+    fn put_hot(&mut self, store: &str, date: String, daily: DailyTransactions, spill: SpillState) {
+        let bytes = daily.memory_size();
+        let tick = self.tick();
+        let entry = HotEntry {
+            daily,
+            bytes,
+            last_access: AtomicU64::new(tick),
+            spill,
+            version: tick,
+        };
+        self.hot_bytes += bytes;
+        if let Some(previous) = self.store_mut(store).hot.insert(date, entry) {
+            self.hot_bytes -= previous.bytes;
+        }
+    }
+
+    /// Catat tanggal yang hanya ada di file spill (scan saat startup)
+    pub fn insert_cold(&mut self, store: &str, date: String, transactions: usize) {
+        let dates = self.store_mut(store);
+        let previous = dates.hot.remove(&date);
+        dates.cold.insert(date, ColdEntry { transactions });
+        if let Some(previous) = previous {
+            self.hot_bytes -= previous.bytes;
+        }
+    }
+
+    /// Hapus tanggal dari memory dan daftar spill; `true` kalau tanggal ada
+    pub fn remove(&mut self, store: &str, date: &str) -> bool {
+        let dates = self.store_mut(store);
+        let hot = dates.hot.remove(date);
+        let cold = dates.cold.remove(date).is_some();
+        if let Some(entry) = &hot {
+            self.hot_bytes -= entry.bytes;
+        }
+        hot.is_some() || cold
+    }
+
+    /// `(store, tanggal)` di memory yang paling lama tidak diakses (lintas store), secukupnya sampai
+    /// pemakaian memory turun ke `budget_bytes`. Kosong kalau masih di bawah batas.
+    pub fn lru_victims(&self, budget_bytes: usize) -> Vec<(String, String)> {
+        if self.hot_bytes <= budget_bytes {
+            return Vec::new();
+        }
+        let mut entries: Vec<(&String, &String, &HotEntry)> = self
+            .stores
+            .iter()
+            .flat_map(|(store, dates)| dates.hot.iter().map(move |(date, entry)| (store, date, entry)))
+            .collect();
+        entries.sort_by_key(|(_, _, entry)| entry.last_access.load(Ordering::Relaxed));
+
+        let mut remaining = self.hot_bytes;
+        entries
+            .into_iter()
+            .take_while(|(_, _, entry)| {
+                let over = remaining > budget_bytes;
+                remaining -= entry.bytes;
+                over
+            })
+            .map(|(store, date, _)| (store.clone(), date.clone()))
+            .collect()
+    }
+
+    /// Status spill tanggal di memory
+    pub fn spill_state(&self, store: &str, date: &str) -> Option<SpillState> {
+        self.hot(store, date).map(|entry| entry.spill)
+    }
+
+    /// Snapshot tanggal yang akan di-evict supaya file spill bisa ditulis tanpa memegang lock cache.
+    /// Tidak mengubah urutan LRU.
+    pub fn spill_candidate(&self, store: &str, date: &str) -> Option<SpillCandidate> {
+        let entry = self.hot(store, date)?;
+        let daily = (entry.spill != SpillState::Current).then(|| entry.daily.clone());
+        Some(SpillCandidate { version: entry.version, daily })
+    }
+
+    /// Keluarkan tanggal dari memory setelah file spill-nya ditulis, hanya kalau isinya masih
+    /// versi yang ditulis (`SpillCandidate::version`)
+    pub fn evict_if_unchanged(&mut self, store: &str, date: &str, version: u64) -> EvictOutcome {
+        let dates = self.store_mut(store);
+        let Some(entry) = dates.hot.get_mut(date) else {
+            return EvictOutcome::Gone;
+        };
+        if entry.version != version {
+            // File spill sekarang ada tapi isinya lebih lama dari memory
+            entry.spill = SpillState::Stale;
+            return EvictOutcome::Changed;
+        }
+        let Some(entry) = dates.hot.remove(date) else {
+            return EvictOutcome::Gone;
+        };
+        dates.cold.insert(date.to_string(), ColdEntry { transactions: entry.daily.len() });
+        self.hot_bytes -= entry.bytes;
+        self.evictions += 1;
+        EvictOutcome::Evicted
+    }
+
+    /// Naik setiap kali tanggal dipindah ke spill; dipakai save untuk tahu apakah ada spill baru
+    /// sejak data di memory ditulis
+    pub fn eviction_count(&self) -> u64 {
+        self.evictions
+    }
+
+    /// Tanggal store ini di memory dengan file spill stale (sudah tercakup file backup setelah save)
+    pub fn stale_spills(&self, store: &str) -> Vec<String> {
+        self.stores
+            .get(store)
+            .map(|dates| {
+                dates.hot
+                    .iter()
+                    .filter(|(_, entry)| entry.spill == SpillState::Stale)
+                    .map(|(date, _)| date.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn clear_spill(&mut self, store: &str, date: &str) {
+        if let Some(entry) = self.stores.get_mut(store).and_then(|dates| dates.hot.get_mut(date)) {
+            entry.spill = SpillState::None;
+        }
+    }
+
+    /// Store yang pernah punya data di cache (termasuk yang sekarang kosong)
+    pub fn stores(&self) -> impl Iterator<Item = &String> {
+        self.stores.keys()
+    }
+
+    /// Semua tanggal store ini (memory + spill), belum diurutkan
+    pub fn dates(&self, store: &str) -> Vec<String> {
+        self.stores
+            .get(store)
+            .map(|dates| dates.hot.keys().chain(dates.cold.keys()).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Tanggal store ini tanpa transaksi (memory + spill)
+    pub fn empty_dates(&self, store: &str) -> Vec<String> {
+        let Some(dates) = self.stores.get(store) else {
+            return Vec::new();
+        };
+        let hot = dates.hot.iter().filter(|(_, entry)| entry.daily.is_empty()).map(|(date, _)| date);
+        let cold = dates.cold.iter().filter(|(_, entry)| entry.transactions == 0).map(|(date, _)| date);
+        hot.chain(cold).cloned().collect()
+    }
+
+    /// Jumlah tanggal store ini (memory + spill)
+    pub fn store_len(&self, store: &str) -> usize {
+        self.stores.get(store).map_or(0, |dates| dates.hot.len() + dates.cold.len())
+    }
+
+    /// Jumlah tanggal semua store (memory + spill)
+    pub fn len(&self) -> usize {
+        self.stores.values().map(|dates| dates.hot.len() + dates.cold.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stores.values().all(|dates| dates.hot.is_empty() && dates.cold.is_empty())
+    }
+
+    /// Jumlah transaksi semua store (memory + spill)
+    pub fn transactions(&self) -> usize {
+        self.stores
+            .values()
+            .map(|dates| {
+                dates.hot.values().map(|entry| entry.daily.len()).sum::<usize>()
+                    + dates.cold.values().map(|entry| entry.transactions).sum::<usize>()
+            })
+            .sum()
+    }
+
+    /// Jumlah tanggal di memory (semua store)
+    pub fn hot_len(&self) -> usize {
+        self.stores.values().map(|dates| dates.hot.len()).sum()
+    }
+
+    /// Ukuran terukur semua tanggal di memory (semua store)
+    pub fn hot_bytes(&self) -> usize {
+        self.hot_bytes
+    }
+
+    /// Salinan tanggal store ini di memory (`tanggal -> transaksi`) untuk ditulis ke file backup
+    /// setelah lock dilepas
+    pub fn hot_dates(&self, store: &str) -> HashMap<String, DailyTransactions> {
+        self.stores
+            .get(store)
+            .map(|dates| dates.hot.iter().map(|(date, entry)| (date.clone(), entry.daily.clone())).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::transaksi;
+
+    fn daily(date: &str, count: usize) -> DailyTransactions {
+        let rows = (0..count).map(|i| transaksi(date, &format!("INV-{}", i), 12500)).collect();
+        DailyTransactions::from_vec(rows).0
+    }
+
+    fn hot_bytes_sum(cache: &CacheStore) -> usize {
+        cache.stores.values().flat_map(|dates| dates.hot.values()).map(|entry| entry.bytes).sum()
+    }
+
+    #[test]
+    fn hot_bytes_follow_every_change() {
+        let mut cache = CacheStore::default();
+        cache.insert("264", "01/10/2025".to_string(), daily("01/10/2025", 3));
+        cache.merge("264", "02/10/2025", vec![transaksi("02/10/2025", "INV-9", 12500)]);
+        cache.merge("265", "01/10/2025", vec![transaksi("01/10/2025", "INV-1", 12500)]);
+        assert_eq!(cache.hot_bytes(), hot_bytes_sum(&cache));
+
+        // Ganti isi tanggal yang sudah ada: ukuran lama dikurangi, bukan ditambah
+        cache.insert("264", "01/10/2025".to_string(), daily("01/10/2025", 10));
+        assert_eq!(cache.hot_bytes(), hot_bytes_sum(&cache));
+
+        let version = cache.spill_candidate("264", "02/10/2025").unwrap().version;
+        assert_eq!(cache.evict_if_unchanged("264", "02/10/2025", version), EvictOutcome::Evicted);
+        assert_eq!(cache.hot_bytes(), hot_bytes_sum(&cache));
+        assert_eq!(cache.transaction_count("264", "02/10/2025"), Some(1));
+
+        assert!(cache.remove("264", "01/10/2025"));
+        cache.insert_cold("265", "01/10/2025".to_string(), 1);
+        assert_eq!(cache.hot_bytes(), 0);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn lru_victims_are_least_recently_used_across_stores() {
+        let mut cache = CacheStore::default();
+        cache.insert("264", "01/10/2025".to_string(), daily("01/10/2025", 5));
+        cache.insert("265", "02/10/2025".to_string(), daily("02/10/2025", 5));
+        cache.insert("264", "03/10/2025".to_string(), daily("03/10/2025", 5));
+        assert!(cache.lru_victims(cache.hot_bytes()).is_empty());
+
+        // Dibaca lagi = paling baru; peek tidak mengubah urutan
+        cache.get("264", "01/10/2025");
+        cache.peek("265", "02/10/2025");
+        let one_date = cache.hot_bytes() - 1;
+        assert_eq!(cache.lru_victims(one_date), vec![("265".to_string(), "02/10/2025".to_string())]);
+        assert_eq!(
+            cache.lru_victims(0),
+            vec![
+                ("265".to_string(), "02/10/2025".to_string()),
+                ("264".to_string(), "03/10/2025".to_string()),
+                ("264".to_string(), "01/10/2025".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn evict_skips_dates_changed_or_removed_after_the_snapshot() {
+        let mut cache = CacheStore::default();
+        cache.insert("264", "01/10/2025".to_string(), daily("01/10/2025", 2));
+        let candidate = cache.spill_candidate("264", "01/10/2025").unwrap();
+        assert_eq!(candidate.daily.as_ref().map(|daily| daily.len()), Some(2));
+
+        // Fetch kosong tidak membatalkan evict
+        cache.merge("264", "01/10/2025", Vec::new());
+        let unchanged = cache.spill_candidate("264", "01/10/2025").unwrap().version;
+        assert_eq!(unchanged, candidate.version);
+
+        cache.merge("264", "01/10/2025", vec![transaksi("01/10/2025", "INV-7", 12500)]);
+        assert_eq!(cache.evict_if_unchanged("264", "01/10/2025", candidate.version), EvictOutcome::Changed);
+        assert!(cache.is_hot("264", "01/10/2025"));
+        assert_eq!(cache.spill_state("264", "01/10/2025"), Some(SpillState::Stale));
+        assert_eq!(cache.eviction_count(), 0);
+
+        let candidate = cache.spill_candidate("264", "01/10/2025").unwrap();
+        cache.remove("264", "01/10/2025");
+        assert_eq!(cache.evict_if_unchanged("264", "01/10/2025", candidate.version), EvictOutcome::Gone);
+        assert!(!cache.contains("264", "01/10/2025"));
+    }
+
+    #[test]
+    fn stale_spills_are_tracked_until_cleared() {
+        let mut cache = CacheStore::default();
+        cache.insert_cold("264", "01/10/2025".to_string(), 2);
+        assert!(cache.promote("264", "01/10/2025", daily("01/10/2025", 2)));
+        assert_eq!(cache.spill_state("264", "01/10/2025"), Some(SpillState::Current));
+        // File spill sudah sama dengan memory: evict tidak perlu menulis ulang
+        assert!(cache.spill_candidate("264", "01/10/2025").unwrap().daily.is_none());
+        assert!(cache.stale_spills("264").is_empty());
+
+        cache.merge("264", "01/10/2025", vec![transaksi("01/10/2025", "INV-5", 12500)]);
+        assert_eq!(cache.stale_spills("264"), vec!["01/10/2025".to_string()]);
+        assert!(cache.spill_candidate("264", "01/10/2025").unwrap().daily.is_some());
+
+        cache.clear_spill("264", "01/10/2025");
+        assert_eq!(cache.spill_state("264", "01/10/2025"), Some(SpillState::None));
+        assert!(cache.stale_spills("264").is_empty());
+
+        // Ganti isi tanggal cold: file spill lama jadi stale
+        cache.insert_cold("264", "02/10/2025".to_string(), 1);
+        cache.insert("264", "02/10/2025".to_string(), daily("02/10/2025", 3));
+        assert_eq!(cache.stale_spills("264"), vec!["02/10/2025".to_string()]);
+    }
+}
@@ -0,0 +1,146 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use super::Money;
+
+#[derive(Deserialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Xlsx,
+    Parquet,
+}
+
+/// Mode streaming untuk range besar: satu transaksi per baris (NDJSON) atau JSON array yang
+/// dikirim chunked. Data dibaca dari cache per tanggal, tidak dikumpulkan dulu di memory.
+#[derive(Deserialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamMode {
+    Ndjson,
+    Array,
+}
+
+/// Query parameter untuk export (`?format=csv&delimiter=;&columns=no_nota,total_tagihan`)
+#[derive(Deserialize, IntoParams, Debug, Clone, Default)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    pub format: Option<ExportFormat>,
+    pub columns: Option<String>,
+    pub delimiter: Option<String>,
+    pub date_format: Option<String>,
+    pub decimal_separator: Option<String>,
+    pub stream: Option<StreamMode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvColumn {
+    TanggalTransaksi,
+    WaktuTransaksi,
+    NoNota,
+    Keterangan,
+    TotalTagihan,
+    Currency,
+    ParseError,
+}
+
+impl CsvColumn {
+    pub const DEFAULT_ORDER: [CsvColumn; 5] = [
+        CsvColumn::TanggalTransaksi,
+        CsvColumn::WaktuTransaksi,
+        CsvColumn::NoNota,
+        CsvColumn::Keterangan,
+        CsvColumn::TotalTagihan,
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim() {
+            "tanggal_transaksi" => Some(CsvColumn::TanggalTransaksi),
+            "waktu_transaksi" => Some(CsvColumn::WaktuTransaksi),
+            "no_nota" => Some(CsvColumn::NoNota),
+            "keterangan" => Some(CsvColumn::Keterangan),
+            "total_tagihan" => Some(CsvColumn::TotalTagihan),
+            "currency" => Some(CsvColumn::Currency),
+            "parse_error" => Some(CsvColumn::ParseError),
+            _ => None,
+        }
+    }
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            CsvColumn::TanggalTransaksi => "tanggal_transaksi",
+            CsvColumn::WaktuTransaksi => "waktu_transaksi",
+            CsvColumn::NoNota => "no_nota",
+            CsvColumn::Keterangan => "keterangan",
+            CsvColumn::TotalTagihan => "total_tagihan",
+            CsvColumn::Currency => "currency",
+            CsvColumn::ParseError => "parse_error",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub columns: Vec<CsvColumn>,
+    pub delimiter: u8,
+    pub date_format: String,
+    pub decimal_separator: char,
+}
+
+impl CsvOptions {
+    /// Delimiter CSV dari `?delimiter=` / `CSV_DELIMITER`: satu karakter ASCII atau alias
+    /// `tab` / `\t` / `semicolon` / `comma`. Dipakai validasi config dan export supaya sama.
+    pub fn parse_delimiter(raw: &str) -> Option<u8> {
+        match raw {
+            "tab" | "\\t" => Some(b'\t'),
+            "semicolon" => Some(b';'),
+            "comma" => Some(b','),
+            other if other.len() == 1 && other.is_ascii() => Some(other.as_bytes()[0]),
+            _ => None,
+        }
+    }
+}
+
+/// Ringkasan per tanggal untuk sheet summary (`date` None = tanggal tidak bisa di-parse)
+#[derive(Debug, Clone)]
+pub struct DailySummary {
+    pub date: Option<NaiveDate>,
+    pub total_transaksi: usize,
+    pub total_tagihan: Money,
+}
+
+/// Request export Parquet ke direktori (tanpa range = seluruh cache)
+#[derive(Deserialize, ToSchema, Debug, Clone, Default)]
+pub struct ParquetExportRequest {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Serialize, ToSchema, Debug, Clone)]
+pub struct PartitionFile {
+    pub month: String,
+    pub path: String,
+    pub rows: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_delimiter_accepts_aliases_and_single_ascii() {
+        assert_eq!(CsvOptions::parse_delimiter("tab"), Some(b'\t'));
+        assert_eq!(CsvOptions::parse_delimiter("\\t"), Some(b'\t'));
+        assert_eq!(CsvOptions::parse_delimiter("\t"), Some(b'\t'));
+        assert_eq!(CsvOptions::parse_delimiter("semicolon"), Some(b';'));
+        assert_eq!(CsvOptions::parse_delimiter("comma"), Some(b','));
+        assert_eq!(CsvOptions::parse_delimiter("|"), Some(b'|'));
+    }
+
+    #[test]
+    fn parse_delimiter_rejects_empty_multi_and_non_ascii() {
+        assert_eq!(CsvOptions::parse_delimiter(""), None);
+        assert_eq!(CsvOptions::parse_delimiter(";;"), None);
+        assert_eq!(CsvOptions::parse_delimiter("§"), None);
+    }
+}
@@ -1,5 +1,23 @@
 pub mod transaction;
 pub mod response;
+pub mod money;
+pub mod audit;
+pub mod export;
+pub mod report;
+pub mod query;
+pub mod archive;
+pub mod cache_file;
+pub mod cache_store;
+#[cfg(test)]
+pub mod test_support;
 
 pub use transaction::*;
-pub use response::*;
\ No newline at end of file
+pub use response::*;
+pub use money::*;
+pub use audit::*;
+pub use export::*;
+pub use report::*;
+pub use query::*;
+pub use archive::*;
+pub use cache_file::*;
+pub use cache_store::*;
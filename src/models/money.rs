@@ -0,0 +1,296 @@
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use utoipa::ToSchema;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::Add;
+use std::str::FromStr;
+
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Currency {
+    #[default]
+    #[serde(rename = "IDR")]
+    Idr,
+}
+
+impl Currency {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Currency::Idr => "IDR",
+        }
+    }
+}
+
+/// Nominal uang dengan mata uang eksplisit. Amount disimpan sebagai `Decimal`
+/// supaya penjumlahan di laporan tetap exact (tanpa pembulatan float).
+#[derive(Serialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Money {
+    pub amount: Decimal,
+    pub currency: Currency,
+}
+
+impl Money {
+    pub fn idr(amount: Decimal) -> Self {
+        Self { amount, currency: Currency::Idr }
+    }
+
+    pub fn zero() -> Self {
+        Self::idr(Decimal::ZERO)
+    }
+
+    /// Parse nominal IDR dari teks upstream. Mendukung format
+    /// `1,234.50` (en-US), `1.234,50` (id-ID), `12.500`, `12500`, prefix `Rp`/`Rp.`
+    /// dan tanda minus sebelum atau sesudah `Rp` (`-Rp 100`, `Rp -100`).
+    pub fn parse_idr(raw: &str) -> Result<Self, String> {
+        // Tanda minus dicek dulu, kalau tidak `-Rp 100` tidak pernah kehilangan prefix `Rp`
+        let (mut negative, rest) = Self::strip_sign(raw.trim());
+        let rest = rest.trim_start();
+        let rest = rest
+            .strip_prefix("Rp")
+            .or_else(|| rest.strip_prefix("rp"))
+            .or_else(|| rest.strip_prefix("RP"))
+            .map(|rest| rest.strip_prefix('.').unwrap_or(rest))
+            .unwrap_or(rest);
+        let rest = if negative {
+            rest
+        } else {
+            let (sign, rest) = Self::strip_sign(rest.trim_start());
+            negative = sign;
+            rest
+        };
+
+        let digits: String = rest.chars().filter(|c| !c.is_whitespace()).collect();
+        if digits.is_empty() {
+            return Err(format!("Empty amount: '{}'", raw));
+        }
+        let digits = digits.as_str();
+
+        if !digits.chars().all(|c| c.is_ascii_digit() || c == '.' || c == ',') {
+            return Err(format!("Invalid amount: '{}'", raw));
+        }
+
+        let decimal_separator = Self::detect_decimal_separator(digits);
+        let canonical: String = digits
+            .chars()
+            .filter_map(|c| match c {
+                '.' | ',' if Some(c) == decimal_separator => Some('.'),
+                '.' | ',' => None,
+                other => Some(other),
+            })
+            .collect();
+
+        let amount = Decimal::from_str(&canonical)
+            .map_err(|e| format!("Invalid amount '{}': {}", raw, e))?;
+
+        Ok(Self::idr(if negative { -amount } else { amount }))
+    }
+
+    fn strip_sign(text: &str) -> (bool, &str) {
+        match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        }
+    }
+
+    /// Bentuk lama `total_tagihan` per transaksi: angka JSON (integer kalau bulat) supaya client
+    /// yang membaca angka tetap jalan. Total di response tetap `{ amount, currency }`.
+    pub fn serialize_number<S: Serializer>(money: &Money, serializer: S) -> Result<S::Ok, S::Error> {
+        let amount = money.amount.normalize();
+        if amount.scale() == 0 {
+            if let Some(integer) = amount.to_i64() {
+                return serializer.serialize_i64(integer);
+            }
+        }
+        let number = amount
+            .to_f64()
+            .ok_or_else(|| serde::ser::Error::custom(format!("Amount {} does not fit a JSON number", amount)))?;
+        serializer.serialize_f64(number)
+    }
+
+    /// Format Indonesia untuk laporan cetak, mis. `Rp 12.500,00`
+    pub fn format_id(&self) -> String {
+        let rounded = self.amount.round_dp(2).abs();
+        let text = format!("{:.2}", rounded);
+        let (integer, fraction) = text.split_once('.').unwrap_or((&text, "00"));
+
+        let mut grouped = String::new();
+        for (i, c) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                grouped.push('.');
+            }
+            grouped.push(c);
+        }
+
+        let sign = if self.amount.is_sign_negative() && !rounded.is_zero() { "-" } else { "" };
+        format!("Rp {}{},{}", sign, grouped, fraction)
+    }
+
+    /// Angka JSON selalu memakai titik desimal, jadi tidak lewat deteksi locale
+    pub fn from_json_number(n: &serde_json::Number) -> Result<Self, String> {
+        let text = n.to_string();
+        Decimal::from_str(&text)
+            .or_else(|_| Decimal::from_scientific(&text))
+            .map(Self::idr)
+            .map_err(|e| format!("Invalid amount '{}': {}", text, e))
+    }
+
+    /// Tentukan separator desimal:
+    /// - dua jenis separator muncul -> yang terakhir adalah desimal
+    /// - satu jenis muncul berkali-kali -> separator ribuan
+    /// - satu jenis muncul sekali diikuti tepat 3 digit -> separator ribuan, kecuali angka di depannya
+    ///   kosong atau diawali `0` (`0,500` = 0.5, grup ribuan tidak pernah diawali nol)
+    /// - selain itu -> desimal
+    fn detect_decimal_separator(digits: &str) -> Option<char> {
+        let last_dot = digits.rfind('.');
+        let last_comma = digits.rfind(',');
+
+        match (last_dot, last_comma) {
+            (Some(dot), Some(comma)) => Some(if dot > comma { '.' } else { ',' }),
+            (Some(pos), None) | (None, Some(pos)) => {
+                let sep = digits[pos..].chars().next()?;
+                let occurrences = digits.matches(sep).count();
+                let fraction_len = digits.len() - pos - 1;
+                let integer = &digits[..pos];
+                let thousands_group = fraction_len == 3 && !integer.is_empty() && !integer.starts_with('0');
+                if occurrences > 1 || thousands_group {
+                    None
+                } else {
+                    Some(sep)
+                }
+            }
+            (None, None) => None,
+        }
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.currency.code(), self.amount)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, rhs: Money) -> Money {
+        Money {
+            amount: self.amount + rhs.amount,
+            currency: self.currency,
+        }
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Self {
+        iter.fold(Money::zero(), |acc, m| acc + m)
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+/// Cache lama menyimpan `total_tagihan` sebagai integer, jadi deserialize
+/// menerima angka, string, maupun bentuk `{ amount, currency }`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MoneyRepr {
+    Full { amount: Decimal, currency: Currency },
+    Number(serde_json::Number),
+    Text(String),
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match MoneyRepr::deserialize(deserializer)? {
+            MoneyRepr::Full { amount, currency } => Ok(Money { amount, currency }),
+            MoneyRepr::Number(n) => Money::from_json_number(&n).map_err(serde::de::Error::custom),
+            MoneyRepr::Text(s) => Money::parse_idr(&s).map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> Decimal {
+        Money::parse_idr(raw).unwrap_or_else(|e| panic!("{}", e)).amount
+    }
+
+    fn dec(text: &str) -> Decimal {
+        Decimal::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn parses_both_locales() {
+        assert_eq!(parse("1.234,56"), dec("1234.56"));
+        assert_eq!(parse("1,234.56"), dec("1234.56"));
+        assert_eq!(parse("12.500,00"), dec("12500"));
+        assert_eq!(parse("12500"), dec("12500"));
+        assert_eq!(parse("12,5"), dec("12.5"));
+    }
+
+    #[test]
+    fn single_separator_before_three_digits_is_thousands() {
+        assert_eq!(parse("12.500"), dec("12500"));
+        assert_eq!(parse("12,500"), dec("12500"));
+        assert_eq!(parse("1.234.567"), dec("1234567"));
+        assert_eq!(parse("1,234,567"), dec("1234567"));
+    }
+
+    #[test]
+    fn leading_zero_means_decimal() {
+        assert_eq!(parse("0,500"), dec("0.5"));
+        assert_eq!(parse("0.500"), dec("0.5"));
+        assert_eq!(parse(",500"), dec("0.5"));
+    }
+
+    #[test]
+    fn handles_rp_prefix_and_sign() {
+        assert_eq!(parse("Rp 12.500"), dec("12500"));
+        assert_eq!(parse("Rp. 12.500,00"), dec("12500"));
+        assert_eq!(parse("-Rp 100"), dec("-100"));
+        assert_eq!(parse("- Rp 1.000"), dec("-1000"));
+        assert_eq!(parse("Rp -100"), dec("-100"));
+        assert_eq!(parse("-12.500"), dec("-12500"));
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        for raw in ["", "Rp", "-", "--100", "12a", "Rp -Rp 5"] {
+            assert!(Money::parse_idr(raw).is_err(), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn format_id_groups_thousands() {
+        assert_eq!(Money::idr(dec("1234567.5")).format_id(), "Rp 1.234.567,50");
+        assert_eq!(Money::idr(dec("-100")).format_id(), "Rp -100,00");
+    }
+
+    #[test]
+    fn deserializes_legacy_and_object_forms() {
+        for json in ["12500", "\"12.500\"", r#"{"amount": "12500", "currency": "IDR"}"#] {
+            let money: Money = serde_json::from_str(json).unwrap();
+            assert_eq!(money.amount, dec("12500"), "{}", json);
+        }
+    }
+
+    #[test]
+    fn serialize_number_keeps_the_integer_shape() {
+        #[derive(Serialize)]
+        struct Row {
+            #[serde(serialize_with = "Money::serialize_number")]
+            total_tagihan: Money,
+        }
+        let json = |amount: &str| serde_json::to_string(&Row { total_tagihan: Money::idr(dec(amount)) }).unwrap();
+        assert_eq!(json("12500.00"), r#"{"total_tagihan":12500}"#);
+        assert_eq!(json("1234.56"), r#"{"total_tagihan":1234.56}"#);
+        let back: Money = serde_json::from_str("1234.56").unwrap();
+        assert_eq!(back.amount, dec("1234.56"));
+    }
+}
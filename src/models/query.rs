@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use super::Money;
+
+#[derive(Deserialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    TanggalTransaksi,
+    WaktuTransaksi,
+    TotalTagihan,
+    NoNota,
+    Keterangan,
+}
+
+#[derive(Deserialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Range tanggal untuk read API `GET /transactions?from=...&to=...`
+#[derive(Deserialize, IntoParams, Debug, Clone, Default)]
+#[into_params(parameter_in = Query)]
+pub struct RangeQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Store yang diminta (`?store_id=264`); kosong = `STORE_ID`, selain itu harus ada di `STORE_IDS`
+#[derive(Deserialize, IntoParams, Debug, Clone, Default)]
+#[into_params(parameter_in = Query)]
+pub struct StoreQuery {
+    pub store_id: Option<String>,
+}
+
+/// Query parameter filter/sort/paging untuk `/data-cached`
+/// (`?q=kopi&min_total=10000&time_from=08:00&sort=total_tagihan&order=desc&limit=50`)
+#[derive(Deserialize, IntoParams, Debug, Clone, Default)]
+#[into_params(parameter_in = Query)]
+pub struct TransactionFilter {
+    pub q: Option<String>,
+    pub min_total: Option<String>,
+    pub max_total: Option<String>,
+    pub time_from: Option<String>,
+    pub time_to: Option<String>,
+    pub sort: Option<SortField>,
+    pub order: Option<SortOrder>,
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+impl TransactionFilter {
+    pub fn is_empty(&self) -> bool {
+        self.q.is_none()
+            && self.min_total.is_none()
+            && self.max_total.is_none()
+            && self.time_from.is_none()
+            && self.time_to.is_none()
+            && self.sort.is_none()
+            && self.limit.is_none()
+            && self.cursor.is_none()
+    }
+}
+
+#[derive(Serialize, ToSchema, Debug, Clone)]
+pub struct PageInfo {
+    pub total_matching: usize,
+    pub total_tagihan_matching: Money,
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub next_cursor: Option<String>,
+}
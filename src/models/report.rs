@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use super::Money;
+
+/// Template laporan penutupan harian (PDF). Bisa di-override lewat file JSON `PDF_TEMPLATE_PATH`,
+/// field yang tidak diisi memakai default (A4, font 9pt).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PdfTemplate {
+    pub title: String,
+    pub store_name: Option<String>,
+    pub header_lines: Vec<String>,
+    pub footer_lines: Vec<String>,
+    pub show_keterangan: bool,
+    pub page_width_mm: f32,
+    pub page_height_mm: f32,
+    pub margin_mm: f32,
+    pub font_size: f32,
+}
+
+impl Default for PdfTemplate {
+    fn default() -> Self {
+        Self {
+            title: "LAPORAN PENUTUPAN HARIAN".to_string(),
+            store_name: None,
+            header_lines: Vec::new(),
+            footer_lines: Vec::new(),
+            show_keterangan: true,
+            page_width_mm: 210.0,
+            page_height_mm: 297.0,
+            margin_mm: 15.0,
+            font_size: 9.0,
+        }
+    }
+}
+
+impl PdfTemplate {
+    /// Cek ukuran halaman/font sebelum dipakai render: semua angka harus finite, font & halaman > 0,
+    /// dan area cetak (lebar dikurangi 2x margin) tidak boleh habis. Kembalikan daftar masalah.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = Vec::new();
+        let sizes = [
+            ("page_width_mm", self.page_width_mm),
+            ("page_height_mm", self.page_height_mm),
+            ("margin_mm", self.margin_mm),
+            ("font_size", self.font_size),
+        ];
+        for (name, value) in sizes {
+            if !value.is_finite() || value < 0.0 {
+                problems.push(format!("{} must be a finite, non-negative number (got {})", name, value));
+            }
+        }
+        if problems.is_empty() {
+            if self.font_size <= 0.0 {
+                problems.push("font_size must be greater than 0".to_string());
+            }
+            if self.page_height_mm <= 2.0 * self.margin_mm {
+                problems.push(format!(
+                    "page_height_mm ({}) must be greater than 2 x margin_mm ({})",
+                    self.page_height_mm, self.margin_mm
+                ));
+            }
+            if self.page_width_mm <= 2.0 * self.margin_mm {
+                problems.push(format!(
+                    "page_width_mm ({}) must be greater than 2 x margin_mm ({})",
+                    self.page_width_mm, self.margin_mm
+                ));
+            }
+        }
+        if problems.is_empty() { Ok(()) } else { Err(problems) }
+    }
+}
+
+#[derive(Deserialize, IntoParams, Debug, Clone)]
+#[into_params(parameter_in = Query)]
+pub struct ClosingReportQuery {
+    pub date: String,
+}
+
+/// Ringkasan satu store di `/report/stores`, hanya dari cache
+#[derive(Serialize, ToSchema, Debug, Clone)]
+pub struct StoreSummary {
+    pub store_id: String,
+    /// Tanggal di range yang ada di cache
+    pub cached_dates: usize,
+    /// Tanggal di range yang belum di-cache (tidak di-fetch oleh endpoint ini)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing_dates: Vec<String>,
+    pub transactions: usize,
+    pub total_tagihan: Money,
+    pub parse_errors: usize,
+}
+
+#[derive(Serialize, ToSchema, Debug, Clone)]
+pub struct StoreSummaryResponse {
+    pub status: String,
+    /// Range yang diminta; kosong = seluruh cache tiap store
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub total_transactions: usize,
+    pub total_tagihan: Money,
+    pub stores: Vec<StoreSummary>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_template_is_valid() {
+        assert!(PdfTemplate::default().validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_zero_font_and_oversized_margin() {
+        let template = PdfTemplate { font_size: 0.0, margin_mm: 120.0, ..PdfTemplate::default() };
+        let problems = template.validate().unwrap_err();
+        assert!(problems.iter().any(|p| p.contains("font_size")));
+        assert!(problems.iter().any(|p| p.contains("page_width_mm")));
+    }
+
+    #[test]
+    fn rejects_negative_and_non_finite_values() {
+        for template in [
+            PdfTemplate { margin_mm: -1.0, ..PdfTemplate::default() },
+            PdfTemplate { font_size: f32::NAN, ..PdfTemplate::default() },
+            PdfTemplate { page_width_mm: f32::INFINITY, ..PdfTemplate::default() },
+        ] {
+            assert!(template.validate().is_err(), "{:?}", template);
+        }
+    }
+}
@@ -1,42 +1,189 @@
-#[allow(dead_code)]
-
+use chrono::{DateTime, Utc};
 use serde::Serialize;
-use super::Transaksi;
+use utoipa::ToSchema;
+use super::{ChangeLogEntry, ChangeSummary, Money, PageInfo, PartitionFile, Transaksi};
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, ToSchema, Debug, Clone)]
 pub struct TransaksiResponse {
     pub total_transaksi: usize,
+    pub total_tagihan: Money,
+    pub parse_errors: usize,
+    pub duplicates_collapsed: usize,
     pub data: Vec<Transaksi>,
 }
 
+impl TransaksiResponse {
+    /// Data di-dedup berdasarkan `no_nota` sebelum dihitung totalnya
+    pub fn new(data: Vec<Transaksi>) -> Self {
+        let (data, duplicates_collapsed) = Transaksi::dedup(data);
+        Self {
+            total_transaksi: data.len(),
+            total_tagihan: Transaksi::total_amount(&data),
+            parse_errors: Transaksi::count_parse_errors(&data),
+            duplicates_collapsed,
+            data,
+        }
+    }
+}
+
 #[derive(Serialize, Debug, Clone)]
 pub struct DebugResponse {
     pub total_transaksi: usize,
+    pub total_tagihan: Money,
+    pub parse_errors: usize,
     pub data: Vec<Transaksi>,
 }
 
-#[derive(Serialize)]
+impl DebugResponse {
+    /// `total_transaksi` adalah totalRow dari server, bukan jumlah `data`
+    pub fn new(total_transaksi: usize, data: Vec<Transaksi>) -> Self {
+        Self {
+            total_transaksi,
+            total_tagihan: Transaksi::total_amount(&data),
+            parse_errors: Transaksi::count_parse_errors(&data),
+            data,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
 pub struct Health {
     pub status: &'static str,
 }
 
-#[derive(Serialize)]
-pub struct EchoResponse {
-    pub echoed: String,
+/// Hasil `/health/ready`: `status` = `ready` atau `degraded`, alasan degraded ada di `problems`
+#[derive(Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    pub status: &'static str,
+    pub problems: Vec<String>,
+    pub checks: ReadinessChecks,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReadinessChecks {
+    pub cache: CacheCheck,
+    pub upstream: UpstreamCheck,
+    pub jobs: JobsCheck,
+    pub persistence: PersistenceCheck,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CacheCheck {
+    /// `pending`, `loaded` atau `failed`
+    pub status: &'static str,
+    pub dates: usize,
+    pub transactions: usize,
+    /// Tanggal di memory dan ukurannya; sisanya ada di spill dir
+    pub memory_dates: usize,
+    pub memory_bytes: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UpstreamCheck {
+    pub last_success_at: Option<DateTime<Utc>>,
+    /// `false` setelah upstream menolak sesi salah satu akun, sampai ada request akun itu yang berhasil lagi
+    pub authorized: bool,
+    /// Penolakan terbaru dari semua akun
+    pub session_rejected_at: Option<DateTime<Utc>>,
+    /// Akun yang sesinya sedang ditolak upstream
+    pub rejected_accounts: Vec<String>,
+    /// Server sudah memegang cookie sesi sendiri (login dengan `USERNAME`/`PASSWORD`)
+    pub server_session: bool,
+    /// Akun yang cookie sesinya dipegang server
+    pub session_accounts: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct JobsCheck {
+    pub running: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PersistenceCheck {
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Serialize)]
-pub struct ApiResponse<T> {
+/// Satu tanggal di memory cache untuk `GET /admin/cache`
+#[derive(Serialize, ToSchema)]
+pub struct CachedDateInfo {
+    pub date: String,
+    pub transactions: usize,
+    pub total_tagihan: Money,
+    pub parse_errors: usize,
+    /// `waktu_transaksi` paling awal dan paling akhir di tanggal itu
+    pub first_transaction_at: Option<String>,
+    pub last_transaction_at: Option<String>,
+    /// Tanggal sedang di-fetch oleh job background
+    pub processing: bool,
+    /// `false` kalau tanggal sudah dipindah ke spill dir (di-load lagi saat diminta)
+    pub in_memory: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AdminCacheResponse {
     pub status: String,
-    pub job_id: Option<String>,
-    pub data: Option<T>,
-    pub message: Option<String>,
+    pub store_id: String,
+    pub total_dates: usize,
+    pub total_transactions: usize,
+    pub dates: Vec<CachedDateInfo>,
 }
 
-#[derive(Serialize)]
+/// Hasil operasi admin; `affected` = jumlah tanggal/flag yang disentuh
+#[derive(Serialize, ToSchema)]
+pub struct AdminOperationResponse {
+    pub status: String,
+    pub operation: String,
+    pub affected: usize,
+    pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dates: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
 pub struct CachedDataResponse {
     pub status: String,
     pub job_id: String,
     pub data: TransaksiResponse,
     pub message: Option<String>,
-}
\ No newline at end of file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<PageInfo>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ForceRefreshResponse {
+    pub success: bool,
+    pub message: String,
+    pub total_transactions: usize,
+    pub total_tagihan: Money,
+    pub parse_errors: usize,
+    pub duplicates_collapsed: usize,
+    pub changes: ChangeSummary,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LoginStatusResponse {
+    pub status: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cookie: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AuditChangesResponse {
+    pub status: String,
+    pub total: usize,
+    pub summary: ChangeSummary,
+    pub changes: Vec<ChangeLogEntry>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ParquetExportResponse {
+    pub status: String,
+    pub directory: String,
+    pub total_rows: usize,
+    pub files: Vec<PartitionFile>,
+}
@@ -0,0 +1,30 @@
+//! Fixture transaksi untuk unit test, dipakai bersama oleh test module di `models` dan `services`
+
+use super::{Money, Transaksi};
+use rust_decimal::Decimal;
+
+/// Transaksi tunai pada `tanggal` jam 10:00:00 dengan nominal `amount` rupiah
+pub fn transaksi(tanggal: &str, no_nota: &str, amount: i64) -> Transaksi {
+    Transaksi {
+        tanggal_transaksi: tanggal.to_string(),
+        waktu_transaksi: format!("{} 10:00:00", tanggal),
+        keterangan: "Tunai".to_string(),
+        total_tagihan: Money::idr(Decimal::new(amount, 0)),
+        no_nota: no_nota.to_string(),
+        parse_error: None,
+    }
+}
+
+impl Transaksi {
+    /// Ganti metode bayar (`keterangan`) fixture
+    pub fn paid_with(mut self, keterangan: &str) -> Self {
+        self.keterangan = keterangan.to_string();
+        self
+    }
+
+    /// Ganti jam transaksi fixture (`HH:MM:SS`), tanggalnya tetap
+    pub fn at(mut self, waktu: &str) -> Self {
+        self.waktu_transaksi = format!("{} {}", self.tanggal_transaksi, waktu);
+        self
+    }
+}
@@ -1,17 +1,225 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use utoipa::ToSchema;
+use std::collections::HashMap;
+use std::ops::Deref;
+use super::Money;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
 pub struct Transaksi {
     pub tanggal_transaksi: String,
     pub waktu_transaksi: String,
     pub keterangan: String,
-    pub total_tagihan: i64,
+    /// Angka JSON (IDR) seperti sebelum ada `Money`; total di response memakai `{ amount, currency }`
+    #[serde(serialize_with = "Money::serialize_number")]
+    #[schema(value_type = f64)]
+    pub total_tagihan: Money,
     pub no_nota: String,
+    // Diisi jika total_tagihan dari upstream gagal di-parse (nilai di-set 0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parse_error: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+impl Transaksi {
+    /// Key unik transaksi: `no_nota`, atau gabungan tanggal/waktu/keterangan/nominal
+    /// kalau `no_nota` kosong
+    pub fn dedup_key(&self) -> String {
+        if !self.no_nota.trim().is_empty() {
+            return self.no_nota.trim().to_string();
+        }
+        format!(
+            "{}|{}|{}|{}",
+            self.tanggal_transaksi, self.waktu_transaksi, self.keterangan, self.total_tagihan.amount
+        )
+    }
+
+    /// Hapus duplikat berdasarkan `dedup_key` (data terakhir menang, urutan pertama dipertahankan).
+    /// Return data unik dan jumlah duplikat yang digabung.
+    pub fn dedup(transactions: Vec<Transaksi>) -> (Vec<Transaksi>, usize) {
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut unique: Vec<Transaksi> = Vec::with_capacity(transactions.len());
+        let mut collapsed = 0;
+
+        for transaksi in transactions {
+            let key = transaksi.dedup_key();
+            match index.get(&key) {
+                Some(&pos) => {
+                    unique[pos] = transaksi;
+                    collapsed += 1;
+                }
+                None => {
+                    index.insert(key, unique.len());
+                    unique.push(transaksi);
+                }
+            }
+        }
+
+        (unique, collapsed)
+    }
+
+    pub fn total_amount(transactions: &[Transaksi]) -> Money {
+        transactions.iter().map(|t| &t.total_tagihan).sum()
+    }
+
+    pub fn count_parse_errors(transactions: &[Transaksi]) -> usize {
+        transactions.iter().filter(|t| t.parse_error.is_some()).count()
+    }
+
+    /// Byte di heap milik transaksi ini (kapasitas string), tanpa `size_of::<Transaksi>()` sendiri
+    pub fn heap_size(&self) -> usize {
+        self.tanggal_transaksi.capacity()
+            + self.waktu_transaksi.capacity()
+            + self.keterangan.capacity()
+            + self.no_nota.capacity()
+            + self.parse_error.as_ref().map_or(0, String::capacity)
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
 pub struct Payload {
     pub from: String,
     pub to: String,
     pub cookie: String,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct MergeStats {
+    pub added: usize,
+    pub updated: usize,
+    pub duplicates_collapsed: usize,
+}
+
+/// Transaksi untuk satu tanggal di cache, dengan index `dedup_key -> posisi`
+/// supaya re-fetch bisa di-merge tanpa menghasilkan duplikat.
+/// Di file backup tetap disimpan sebagai array `Transaksi` biasa.
+#[derive(Debug, Clone, Default)]
+pub struct DailyTransactions {
+    transactions: Vec<Transaksi>,
+    index: HashMap<String, usize>,
+}
+
+impl DailyTransactions {
+    pub fn from_vec(transactions: Vec<Transaksi>) -> (Self, usize) {
+        let mut daily = Self::default();
+        let stats = daily.merge(transactions);
+        (daily, stats.duplicates_collapsed)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Transaksi> {
+        self.index.get(key).map(|&pos| &self.transactions[pos])
+    }
+
+    /// Ukuran di memory: slot `Vec`, string tiap transaksi, dan index (bucket + key).
+    /// Dihitung ulang setelah merge; dipakai untuk batas `CACHE_MEMORY_BUDGET_MB`.
+    pub fn memory_size(&self) -> usize {
+        let rows = self.transactions.capacity() * size_of::<Transaksi>()
+            + self.transactions.iter().map(Transaksi::heap_size).sum::<usize>();
+        // hashbrown: satu slot (key, value) + satu control byte per bucket
+        let index = self.index.capacity() * (size_of::<(String, usize)>() + 1)
+            + self.index.keys().map(String::capacity).sum::<usize>();
+        size_of::<Self>() + rows + index
+    }
+
+    /// Merge data baru: `no_nota` yang sudah ada di-update, yang baru di-append,
+    /// data lama yang tidak ada di fetch baru tetap disimpan
+    pub fn merge(&mut self, incoming: Vec<Transaksi>) -> MergeStats {
+        let (incoming, duplicates_collapsed) = Transaksi::dedup(incoming);
+        let mut stats = MergeStats { duplicates_collapsed, ..Default::default() };
+
+        for transaksi in incoming {
+            let key = transaksi.dedup_key();
+            match self.index.get(&key) {
+                Some(&pos) => {
+                    self.transactions[pos] = transaksi;
+                    stats.updated += 1;
+                }
+                None => {
+                    self.index.insert(key, self.transactions.len());
+                    self.transactions.push(transaksi);
+                    stats.added += 1;
+                }
+            }
+        }
+
+        stats
+    }
+}
+
+impl Deref for DailyTransactions {
+    type Target = [Transaksi];
+
+    fn deref(&self) -> &[Transaksi] {
+        &self.transactions
+    }
+}
+
+impl Serialize for DailyTransactions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.transactions.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DailyTransactions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let transactions = Vec::<Transaksi>::deserialize(deserializer)?;
+        Ok(Self::from_vec(transactions).0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::transaksi;
+    use rust_decimal::Decimal;
+
+    fn notas(transactions: &[Transaksi]) -> Vec<(&str, &str)> {
+        transactions.iter().map(|t| (t.no_nota.as_str(), t.keterangan.as_str())).collect()
+    }
+
+    #[test]
+    fn dedup_keeps_first_position_and_last_data() {
+        let (unique, collapsed) = Transaksi::dedup(vec![
+            transaksi("01/10/2025", "INV-1", 1000),
+            transaksi("01/10/2025", "INV-2", 2000).paid_with("QRIS"),
+            transaksi("01/10/2025", " INV-1 ", 1500).paid_with("Debit"),
+        ]);
+        assert_eq!(collapsed, 1);
+        assert_eq!(notas(&unique), vec![(" INV-1 ", "Debit"), ("INV-2", "QRIS")]);
+    }
+
+    #[test]
+    fn dedup_without_no_nota_uses_the_other_fields() {
+        let (unique, collapsed) = Transaksi::dedup(vec![
+            transaksi("01/10/2025", "", 1000),
+            transaksi("01/10/2025", "  ", 1000),
+            transaksi("01/10/2025", "", 2000),
+        ]);
+        assert_eq!(collapsed, 1);
+        assert_eq!(unique.len(), 2);
+        assert_eq!(unique[1].total_tagihan, Money::idr(Decimal::new(2000, 0)));
+    }
+
+    #[test]
+    fn merge_updates_existing_and_keeps_missing() {
+        let (mut daily, collapsed) = DailyTransactions::from_vec(vec![
+            transaksi("01/10/2025", "INV-1", 1000),
+            transaksi("01/10/2025", "INV-2", 2000).paid_with("QRIS"),
+            transaksi("01/10/2025", "INV-2", 2000).paid_with("QRIS"),
+        ]);
+        assert_eq!(collapsed, 1);
+
+        // INV-2 tidak ada di fetch baru tapi tetap disimpan
+        let stats = daily.merge(vec![
+            transaksi("01/10/2025", "INV-1", 1000).paid_with("Debit"),
+            transaksi("01/10/2025", "INV-3", 3000),
+            transaksi("01/10/2025", "INV-3", 3500),
+        ]);
+        assert_eq!((stats.added, stats.updated, stats.duplicates_collapsed), (1, 1, 1));
+        assert_eq!(notas(&daily), vec![("INV-1", "Debit"), ("INV-2", "QRIS"), ("INV-3", "Tunai")]);
+        assert_eq!(daily[2].total_tagihan, Money::idr(Decimal::new(3500, 0)));
+
+        // Merge berikutnya memakai index yang sama, tidak menambah duplikat
+        let stats = daily.merge(vec![transaksi("01/10/2025", "INV-3", 4000)]);
+        assert_eq!((stats.added, stats.updated), (0, 1));
+        assert_eq!(daily.len(), 3);
+    }
+}
@@ -0,0 +1,83 @@
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::errors::{ErrorBody, ErrorCode, ErrorResponse};
+use crate::handlers::{self, LoginRequest};
+use crate::models::{
+    AdminCacheResponse, AdminOperationResponse, ArchiveDateEntry, ArchiveManifest, AuditChangesResponse, CacheCheck,
+    CacheImportResponse, CachedDataResponse, CachedDateInfo, ChangeKind, ChangeLogEntry, ChangeSummary, Currency,
+    ExportFormat, ForceRefreshResponse, Health, ImportPolicy, ImportSummary, JobsCheck, LoginStatusResponse, Money,
+    PageInfo, ParquetExportRequest, ParquetExportResponse, PartitionFile, Payload, PersistenceCheck, ReadinessChecks,
+    ReadinessResponse, SortField, SortOrder, StoreSummary, StoreSummaryResponse, StreamMode, Transaksi, TransaksiResponse, UpstreamCheck,
+};
+
+/// Dokumen OpenAPI 3 untuk semua route di `main.rs`, disajikan di `/openapi.json`
+/// supaya frontend bisa generate typed client
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Pass Cookie Report API"),
+    paths(
+        handlers::root,
+        handlers::health_live,
+        handlers::health_ready,
+        handlers::get_openapi,
+        handlers::get_metrics,
+        handlers::get_cached_data,
+        handlers::get_transactions,
+        handlers::get_transactions_by_date,
+        handlers::get_transaction_by_nota,
+        handlers::export_data,
+        handlers::export_parquet_partitions,
+        handlers::get_closing_report,
+        handlers::get_store_summary,
+        handlers::force_refresh_data,
+        handlers::get_login_status,
+        handlers::post_login,
+        handlers::get_audit_changes,
+        handlers::admin_list_cache,
+        handlers::admin_evict_cache,
+        handlers::admin_clean_empty_cache,
+        handlers::admin_flush_cache,
+        handlers::admin_clear_processing,
+        handlers::admin_export_cache,
+        handlers::admin_import_cache,
+    ),
+    components(schemas(
+        ErrorResponse, ErrorBody, ErrorCode,
+        Health, ReadinessResponse, ReadinessChecks, CacheCheck, UpstreamCheck, JobsCheck, PersistenceCheck,
+        Payload, Transaksi, Money, Currency, TransaksiResponse, CachedDataResponse, PageInfo,
+        ExportFormat, StreamMode, SortField, SortOrder,
+        ParquetExportRequest, ParquetExportResponse, PartitionFile,
+        StoreSummary, StoreSummaryResponse,
+        ForceRefreshResponse, LoginRequest, LoginStatusResponse,
+        ChangeKind, ChangeLogEntry, ChangeSummary, AuditChangesResponse,
+        CachedDateInfo, AdminCacheResponse, AdminOperationResponse,
+        ArchiveManifest, ArchiveDateEntry, ImportPolicy, ImportSummary, CacheImportResponse,
+    )),
+    tags(
+        (name = "health", description = "Liveness, readiness, metrics and API description"),
+        (name = "transactions", description = "Cached transaction data with smart fetch"),
+        (name = "export", description = "CSV, XLSX and Parquet exports"),
+        (name = "report", description = "Printable reports"),
+        (name = "cache", description = "Cache maintenance"),
+        (name = "auth", description = "Upstream session"),
+        (name = "audit", description = "Changes detected by force refresh"),
+        (name = "admin", description = "Cache maintenance, requires `Authorization: Bearer <ADMIN_TOKEN>`"),
+    ),
+    modifiers(&AdminTokenSecurity)
+)]
+pub struct ApiDoc;
+
+/// Skema `admin_token` (Bearer) yang dipakai route `/admin/*`
+struct AdminTokenSecurity;
+
+impl Modify for AdminTokenSecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "admin_token",
+                SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+            );
+        }
+    }
+}
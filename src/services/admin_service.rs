@@ -0,0 +1,156 @@
+use crate::errors::DebugAppError;
+use crate::models::{CachedDateInfo, Transaksi};
+use crate::services::{CacheService, DateService};
+use crate::state::AppState;
+use std::collections::HashSet;
+use std::future::Future;
+use tracing::info;
+
+pub struct AdminService;
+
+impl AdminService {
+    /// Jalankan operasi admin dengan lock single-instance per nama operasi (`start_admin_operation`).
+    /// Request kedua untuk operasi yang sama ditolak dengan `409` selama yang pertama belum selesai.
+    pub async fn exclusive<T, F, Fut>(state: &AppState, operation: &str, op: F) -> Result<T, DebugAppError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, DebugAppError>>,
+    {
+        let Some(_guard) = state.start_admin_operation(operation) else {
+            return Err(DebugAppError::Conflict(format!("Admin operation '{}' is already running", operation)));
+        };
+        op().await
+    }
+
+    /// Tanggal di cache (opsional dibatasi `from`/`to`), urut tanggal, dengan jumlah dan total per hari.
+    /// Tanggal di spill dibaca dari disk tanpa dimasukkan lagi ke memory.
+    pub async fn list_cached_dates(state: &AppState, store: &str, from: Option<&str>, to: Option<&str>) -> Result<Vec<CachedDateInfo>, DebugAppError> {
+        let dates = CacheService::dates_in_range(state, store, from, to).await?;
+        let in_memory: HashSet<String> = {
+            let cache = state.cache.read().await;
+            dates.iter().filter(|date| cache.is_hot(store, date)).cloned().collect()
+        };
+        let processing = state.processing.read().await.clone();
+
+        let mut infos = Vec::new();
+        CacheService::for_each_cached(state, store, &dates, false, |date, daily| {
+            let times = daily.iter().filter_map(|t| {
+                DateService::parse_datetime(&t.waktu_transaksi).map(|parsed| (parsed, &t.waktu_transaksi))
+            });
+            let first = times.clone().min_by_key(|(parsed, _)| *parsed).map(|(_, raw)| raw.clone());
+            let last = times.max_by_key(|(parsed, _)| *parsed).map(|(_, raw)| raw.clone());
+            infos.push(CachedDateInfo {
+                date: date.to_string(),
+                transactions: daily.len(),
+                total_tagihan: Transaksi::total_amount(daily),
+                parse_errors: Transaksi::count_parse_errors(daily),
+                first_transaction_at: first,
+                last_transaction_at: last,
+                processing: processing.get(&AppState::processing_key(store, date)).copied().unwrap_or(false),
+                in_memory: in_memory.contains(date),
+            });
+        })
+        .await?;
+        Ok(infos)
+    }
+
+    /// Hapus range dari cache (memory dan spill) lalu tulis ulang file backup, tanpa fetch ulang ke upstream.
+    /// Tanggal yang dihapus akan di-fetch lagi saat diminta berikutnya.
+    pub async fn evict_range(state: &AppState, store: &str, from: &str, to: &str) -> Result<Vec<String>, DebugAppError> {
+        let dates = DateService::get_date_range(from, to)
+            .map_err(|_| DebugAppError::DateParse("Invalid date format".to_string()))?;
+
+        let evicted = Self::remove_dates(state, store, dates).await;
+        if !evicted.is_empty() {
+            CacheService::save_cache_to_file(state).await?;
+        }
+        info!(store = %store, from = %from, to = %to, evicted = evicted.len(), "[ADMIN] Evicted cached dates");
+        Ok(evicted)
+    }
+
+    /// Buang tanggal tanpa transaksi dari memory dan dari file backup, untuk semua store.
+    /// Mengembalikan tanggal yang dibuang dari memory (diawali `<store>/` untuk store selain default)
+    /// dan jumlah entry yang dibuang dari file.
+    pub async fn clean_empty(state: &AppState) -> Result<(Vec<String>, usize), DebugAppError> {
+        let config = state.config();
+        let mut removed = Vec::new();
+        let mut cleaned_in_file = 0;
+        for store in config.stores() {
+            let empty = state.cache.read().await.empty_dates(&store);
+            let dates = Self::remove_dates(state, &store, empty).await;
+            if config.is_default_store(&store) {
+                removed.extend(dates);
+            } else {
+                removed.extend(dates.into_iter().map(|date| format!("{}/{}", store, date)));
+            }
+            cleaned_in_file += CacheService::clean_empty_cache_entries(state, &store).await?;
+        }
+        info!(memory = removed.len(), file = cleaned_in_file, "[ADMIN] Cleaned empty cache entries");
+        Ok((removed, cleaned_in_file))
+    }
+
+    /// Tulis memory cache semua store ke file sekarang; mengembalikan jumlah tanggal yang tersimpan (file backup + spill)
+    pub async fn flush(state: &AppState) -> Result<usize, DebugAppError> {
+        CacheService::save_cache_to_file(state).await?;
+        Ok(state.cache.read().await.len())
+    }
+
+    async fn remove_dates(state: &AppState, store: &str, dates: Vec<String>) -> Vec<String> {
+        let config = state.config();
+        let spill = state.spill_lock.lock().await;
+        let mut removed = Vec::new();
+        for date in dates {
+            if CacheService::remove_date(state, &config, store, &date, &spill).await {
+                removed.push(date);
+            }
+        }
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn exclusive_rejects_a_second_run_of_the_same_operation() {
+        let state = AppState::new(Config::default());
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel::<()>();
+        let running = {
+            let state = state.clone();
+            tokio::spawn(async move {
+                AdminService::exclusive(&state, "flush_cache", || async {
+                    let _ = started_tx.send(());
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    Ok(())
+                }).await
+            })
+        };
+        started_rx.await.unwrap();
+
+        let second = AdminService::exclusive(&state, "flush_cache", || async { Ok(()) }).await;
+        assert!(matches!(second, Err(DebugAppError::Conflict(_))));
+        let other = AdminService::exclusive(&state, "list_cache", || async { Ok(()) }).await;
+        assert!(other.is_ok());
+
+        running.await.unwrap().unwrap();
+        assert!(AdminService::exclusive(&state, "flush_cache", || async { Ok(()) }).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn exclusive_releases_the_operation_when_the_request_is_dropped() {
+        let state = AppState::new(Config::default());
+        let cancelled = tokio::time::timeout(
+            Duration::from_millis(10),
+            AdminService::exclusive(&state, "export_cache", || async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok(())
+            }),
+        ).await;
+        assert!(cancelled.is_err());
+
+        assert!(AdminService::exclusive(&state, "export_cache", || async { Ok(()) }).await.is_ok());
+    }
+}
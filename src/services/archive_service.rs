@@ -0,0 +1,367 @@
+use crate::errors::DebugAppError;
+use crate::models::{
+    ArchiveDateEntry, ArchiveManifest, DailyTransactions, ImportPolicy, ImportSummary, Transaksi,
+    CACHE_ARCHIVE_SCHEMA_VERSION,
+};
+use crate::services::{CacheService, DateService};
+use crate::state::AppState;
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Read;
+use tracing::{info, warn};
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const DATA_ENTRY: &str = "cache.json";
+
+/// Batas ukuran archive yang di-upload ke `/admin/cache/import`
+pub const MAX_ARCHIVE_BYTES: usize = 256 * 1024 * 1024;
+/// Batas ukuran satu entry setelah dekompresi (melindungi dari gzip bomb). JSON cache terkompresi
+/// sekitar 5-10x, jadi 4x batas upload cukup untuk archive yang wajar tanpa memakan memory berlebihan.
+const MAX_ENTRY_BYTES: u64 = 4 * MAX_ARCHIVE_BYTES as u64;
+
+pub struct ArchiveService;
+
+impl ArchiveService {
+    /// Export cache satu store (opsional range `from`/`to`) sebagai `.tar.gz` berisi `manifest.json` dan `cache.json`
+    pub async fn export(state: &AppState, store: &str, from: Option<&str>, to: Option<&str>) -> Result<(ArchiveManifest, Vec<u8>), DebugAppError> {
+        let dates = CacheService::dates_in_range(state, store, from, to).await?;
+        let mut selected: Vec<(String, Vec<Transaksi>)> = Vec::new();
+        CacheService::for_each_cached(state, store, &dates, false, |date, daily| selected.push((date.to_string(), daily.to_vec()))).await?;
+        let store_id = store.to_string();
+        let (from, to) = (from.map(str::to_string), to.map(str::to_string));
+
+        let (manifest, bytes) = tokio::task::spawn_blocking(move || Self::build_archive(store_id, from, to, selected))
+            .await
+            .map_err(|e| DebugAppError::Serialization(format!("Archive task failed: {}", e)))??;
+        info!(
+            store = %manifest.store_id,
+            dates = manifest.total_dates,
+            transactions = manifest.total_transactions,
+            bytes = bytes.len(),
+            "[ARCHIVE] Exported cache archive"
+        );
+        Ok((manifest, bytes))
+    }
+
+    /// Validasi archive lalu gabungkan ke memory cache store archive sesuai `policy`, kemudian simpan ke file.
+    /// Store archive harus ada di `STORE_ID`/`STORE_IDS`; kalau `store` diisi, harus sama dengan store archive.
+    pub async fn import(state: &AppState, archive: Vec<u8>, policy: ImportPolicy, store: Option<&str>) -> Result<(ArchiveManifest, ImportSummary), DebugAppError> {
+        let (manifest, data) = tokio::task::spawn_blocking(move || Self::read_archive(&archive))
+            .await
+            .map_err(|e| DebugAppError::Serialization(format!("Archive task failed: {}", e)))??;
+
+        let config = state.config();
+        let store_id = config.resolve_store(Some(store.unwrap_or(&manifest.store_id)))?;
+        if manifest.store_id != store_id {
+            return Err(DebugAppError::InvalidInput(format!(
+                "Archive belongs to store '{}', not store '{}'",
+                manifest.store_id, store_id
+            )));
+        }
+
+        let mut summary = ImportSummary { policy, ..Default::default() };
+        {
+            // Tahan evict selama import; file spill dibaca tanpa lock cache (`ensure_hot`)
+            let spill = state.spill_lock.lock().await;
+            // Merge: load semua tanggal yang ada di spill dulu. Kalau satu gagal, import batal sebelum
+            // ada yang diubah, jadi tidak ada import setengah jalan yang tidak tersimpan.
+            if policy == ImportPolicy::Merge {
+                for date in data.keys() {
+                    CacheService::ensure_hot(state, &config, &store_id, date, &spill).await?;
+                }
+            }
+
+            let mut cache = state.cache.write().await;
+            for (date, transactions) in data {
+                let count = transactions.len();
+                match (cache.contains(&store_id, &date), policy) {
+                    (false, _) => {
+                        let (daily, collapsed) = DailyTransactions::from_vec(transactions);
+                        summary.duplicates_collapsed += collapsed;
+                        cache.insert(&store_id, date, daily);
+                        summary.added += 1;
+                    }
+                    (true, ImportPolicy::SkipExisting) => {
+                        summary.skipped += 1;
+                        continue;
+                    }
+                    (true, ImportPolicy::Merge) => {
+                        summary.duplicates_collapsed += cache.merge(&store_id, &date, transactions).duplicates_collapsed;
+                        summary.merged += 1;
+                    }
+                    (true, ImportPolicy::Overwrite) => {
+                        let (daily, collapsed) = DailyTransactions::from_vec(transactions);
+                        summary.duplicates_collapsed += collapsed;
+                        cache.insert(&store_id, date, daily);
+                        summary.overwritten += 1;
+                    }
+                }
+                summary.transactions += count;
+            }
+        }
+        CacheService::enforce_memory_budget(state).await;
+
+        if summary.added + summary.merged + summary.overwritten > 0 {
+            CacheService::save_cache_to_file(state).await?;
+        }
+        info!(
+            store = %store_id,
+            policy = ?policy,
+            added = summary.added,
+            merged = summary.merged,
+            overwritten = summary.overwritten,
+            skipped = summary.skipped,
+            "[ARCHIVE] Imported cache archive"
+        );
+        Ok((manifest, summary))
+    }
+
+    /// Nama file download, mis. `cache_store263_01-10-2025_31-10-2025.tar.gz`
+    pub fn filename(manifest: &ArchiveManifest) -> String {
+        let coverage = match (&manifest.first_date, &manifest.last_date) {
+            (Some(first), Some(last)) => format!("{}_{}", first.replace('/', "-"), last.replace('/', "-")),
+            _ => "empty".to_string(),
+        };
+        format!("cache_store{}_{}.tar.gz", manifest.store_id, coverage)
+    }
+
+    /// Blocking (kompresi), panggil lewat `spawn_blocking`
+    fn build_archive(
+        store_id: String,
+        from: Option<String>,
+        to: Option<String>,
+        selected: Vec<(String, Vec<Transaksi>)>,
+    ) -> Result<(ArchiveManifest, Vec<u8>), DebugAppError> {
+        let mut entries = Vec::with_capacity(selected.len());
+        for (date, transactions) in &selected {
+            entries.push(ArchiveDateEntry {
+                date: date.clone(),
+                transactions: transactions.len(),
+                sha256: Self::date_checksum(transactions)?,
+            });
+        }
+        let data: BTreeMap<&str, &Vec<Transaksi>> = selected.iter().map(|(date, t)| (date.as_str(), t)).collect();
+        let data_json = serde_json::to_vec(&data).map_err(|e| DebugAppError::Serialization(e.to_string()))?;
+
+        let manifest = ArchiveManifest {
+            schema_version: CACHE_ARCHIVE_SCHEMA_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: Utc::now(),
+            store_id,
+            from,
+            to,
+            first_date: selected.first().map(|(date, _)| date.clone()),
+            last_date: selected.last().map(|(date, _)| date.clone()),
+            total_dates: selected.len(),
+            total_transactions: entries.iter().map(|e| e.transactions).sum(),
+            data_sha256: format!("{:x}", Sha256::digest(&data_json)),
+            dates: entries,
+        };
+        let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(|e| DebugAppError::Serialization(e.to_string()))?;
+
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (name, content) in [(MANIFEST_ENTRY, &manifest_json), (DATA_ENTRY, &data_json)] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(manifest.created_at.timestamp().max(0) as u64);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, content.as_slice())
+                .map_err(|e| DebugAppError::FileWrite(e.to_string()))?;
+        }
+        let bytes = builder
+            .into_inner()
+            .and_then(|gz| gz.finish())
+            .map_err(|e| DebugAppError::FileWrite(e.to_string()))?;
+        Ok((manifest, bytes))
+    }
+
+    /// Buka dan validasi archive: versi schema, checksum file dan per tanggal, key tanggal, dan total.
+    /// Blocking (dekompresi), panggil lewat `spawn_blocking`.
+    fn read_archive(archive: &[u8]) -> Result<(ArchiveManifest, BTreeMap<String, Vec<Transaksi>>), DebugAppError> {
+        let invalid = |message: String| DebugAppError::InvalidInput(format!("Invalid cache archive: {}", message));
+
+        let mut manifest_json = None;
+        let mut data_json = None;
+        let mut tar = tar::Archive::new(GzDecoder::new(archive));
+        for entry in tar.entries().map_err(|e| invalid(e.to_string()))? {
+            let entry = entry.map_err(|e| invalid(e.to_string()))?;
+            let name = entry.path().map_err(|e| invalid(e.to_string()))?.to_string_lossy().to_string();
+            let target = match name.as_str() {
+                MANIFEST_ENTRY => &mut manifest_json,
+                DATA_ENTRY => &mut data_json,
+                other => {
+                    warn!(entry = %other, "[ARCHIVE] Ignoring unknown archive entry");
+                    continue;
+                }
+            };
+            let mut content = Vec::new();
+            entry
+                .take(MAX_ENTRY_BYTES + 1)
+                .read_to_end(&mut content)
+                .map_err(|e| invalid(e.to_string()))?;
+            if content.len() as u64 > MAX_ENTRY_BYTES {
+                return Err(invalid(format!("{} is larger than {} bytes", name, MAX_ENTRY_BYTES)));
+            }
+            *target = Some(content);
+        }
+        let manifest_json = manifest_json.ok_or_else(|| invalid(format!("{} is missing", MANIFEST_ENTRY)))?;
+        let data_json = data_json.ok_or_else(|| invalid(format!("{} is missing", DATA_ENTRY)))?;
+
+        let manifest: ArchiveManifest = serde_json::from_slice(&manifest_json)
+            .map_err(|e| invalid(format!("{}: {}", MANIFEST_ENTRY, e)))?;
+        if manifest.schema_version == 0 || manifest.schema_version > CACHE_ARCHIVE_SCHEMA_VERSION {
+            return Err(invalid(format!(
+                "schema version {} is not supported (this build reads up to {})",
+                manifest.schema_version, CACHE_ARCHIVE_SCHEMA_VERSION
+            )));
+        }
+
+        let data_sha256 = format!("{:x}", Sha256::digest(&data_json));
+        if data_sha256 != manifest.data_sha256 {
+            return Err(invalid(format!("{} checksum mismatch", DATA_ENTRY)));
+        }
+        let data: BTreeMap<String, Vec<Transaksi>> = serde_json::from_slice(&data_json)
+            .map_err(|e| invalid(format!("{}: {}", DATA_ENTRY, e)))?;
+
+        let manifest_dates: BTreeSet<&str> = manifest.dates.iter().map(|e| e.date.as_str()).collect();
+        let data_dates: BTreeSet<&str> = data.keys().map(String::as_str).collect();
+        if manifest_dates != data_dates || manifest_dates.len() != manifest.dates.len() {
+            return Err(invalid("dates in manifest and data do not match".to_string()));
+        }
+        for entry in &manifest.dates {
+            // Key harus format cache (DD/MM/YYYY), kalau tidak data tidak akan pernah ketemu saat lookup
+            if DateService::normalize_date_for_api(&entry.date).ok().as_deref() != Some(entry.date.as_str()) {
+                return Err(invalid(format!("'{}' is not a DD/MM/YYYY cache key", entry.date)));
+            }
+            let transactions = &data[&entry.date];
+            if transactions.len() != entry.transactions || Self::date_checksum(transactions)? != entry.sha256 {
+                return Err(invalid(format!("checksum mismatch for {}", entry.date)));
+            }
+        }
+        let total_transactions: usize = data.values().map(Vec::len).sum();
+        if manifest.total_dates != data.len() || manifest.total_transactions != total_transactions {
+            return Err(invalid("totals in manifest do not match the data".to_string()));
+        }
+
+        Ok((manifest, data))
+    }
+
+    fn date_checksum(transactions: &[Transaksi]) -> Result<String, DebugAppError> {
+        let json = serde_json::to_vec(transactions).map_err(|e| DebugAppError::Serialization(e.to_string()))?;
+        Ok(format!("{:x}", Sha256::digest(&json)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::transaksi;
+    use crate::services::CacheSpillService;
+
+    fn selected() -> Vec<(String, Vec<Transaksi>)> {
+        vec![
+            ("01/10/2025".to_string(), vec![transaksi("01/10/2025", "INV-1", 12500)]),
+            ("02/10/2025".to_string(), vec![transaksi("02/10/2025", "INV-2", 5000), transaksi("02/10/2025", "INV-3", 7000)]),
+        ]
+    }
+
+    fn tar_gz(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (name, content) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *content).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn expect_invalid(archive: &[u8], expected: &str) {
+        match ArchiveService::read_archive(archive) {
+            Err(DebugAppError::InvalidInput(message)) => assert!(message.contains(expected), "{}", message),
+            other => panic!("expected '{}', got {:?}", expected, other.map(|(manifest, _)| manifest.total_dates)),
+        }
+    }
+
+    #[test]
+    fn archive_round_trips() {
+        let (manifest, bytes) = ArchiveService::build_archive("264".to_string(), None, None, selected()).unwrap();
+        assert_eq!(manifest.total_dates, 2);
+        assert_eq!(manifest.total_transactions, 3);
+
+        let (read, data) = ArchiveService::read_archive(&bytes).unwrap();
+        assert_eq!(read.store_id, "264");
+        assert_eq!(read.data_sha256, manifest.data_sha256);
+        assert_eq!(data["02/10/2025"].len(), 2);
+        assert_eq!(data["01/10/2025"][0].no_nota, "INV-1");
+    }
+
+    #[test]
+    fn rejects_data_that_does_not_match_the_checksums() {
+        let (manifest, _) = ArchiveService::build_archive("264".to_string(), None, None, selected()).unwrap();
+        let mut data: BTreeMap<String, Vec<Transaksi>> = selected().into_iter().collect();
+        data.get_mut("01/10/2025").unwrap()[0] = transaksi("01/10/2025", "INV-1", 99999);
+        let data_json = serde_json::to_vec(&data).unwrap();
+
+        // Manifest lama: checksum seluruh cache.json tidak cocok
+        let manifest_json = serde_json::to_vec(&manifest).unwrap();
+        expect_invalid(&tar_gz(&[(MANIFEST_ENTRY, &manifest_json), (DATA_ENTRY, &data_json)]), "cache.json checksum mismatch");
+
+        // Checksum cache.json ikut diganti: checksum per tanggal tetap menangkap perubahan
+        let mut forged = manifest.clone();
+        forged.data_sha256 = format!("{:x}", Sha256::digest(&data_json));
+        let manifest_json = serde_json::to_vec(&forged).unwrap();
+        expect_invalid(&tar_gz(&[(MANIFEST_ENTRY, &manifest_json), (DATA_ENTRY, &data_json)]), "checksum mismatch for 01/10/2025");
+    }
+
+    #[test]
+    fn rejects_missing_entries_and_foreign_date_keys() {
+        let (manifest, _) = ArchiveService::build_archive("264".to_string(), None, None, selected()).unwrap();
+        let manifest_json = serde_json::to_vec(&manifest).unwrap();
+        expect_invalid(&tar_gz(&[(MANIFEST_ENTRY, &manifest_json)]), "cache.json is missing");
+        expect_invalid(&tar_gz(&[(DATA_ENTRY, b"{}")]), "manifest.json is missing");
+
+        let (manifest, bytes) = ArchiveService::build_archive(
+            "264".to_string(),
+            None,
+            None,
+            vec![("2025-10-01".to_string(), vec![transaksi("2025-10-01", "INV-1", 12500)])],
+        )
+        .unwrap();
+        assert_eq!(manifest.total_dates, 1);
+        expect_invalid(&bytes, "is not a DD/MM/YYYY cache key");
+    }
+
+    #[tokio::test]
+    async fn merge_import_changes_nothing_when_a_spilled_date_cannot_be_loaded() {
+        let dir = std::env::temp_dir().join(format!("archive_merge_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut config = crate::config::Config::default();
+        config.upstream.store_id = "264".to_string();
+        config.cache.file_path = dir.join("cache_backup.json").to_string_lossy().to_string();
+        config.cache.spill_dir = dir.join("spill").to_string_lossy().to_string();
+        let spill_dir = config.cache.spill_dir.clone();
+        let state = AppState::new(config);
+
+        // 01/10/2025 belum ada di cache; 02/10/2025 (diproses sesudahnya) hanya ada di spill yang rusak
+        std::fs::create_dir_all(&spill_dir).unwrap();
+        std::fs::write(CacheSpillService::path(&spill_dir, "02/10/2025").unwrap(), "{ not json").unwrap();
+        state.cache.write().await.insert_cold("264", "02/10/2025".to_string(), 2);
+
+        let (_, archive) = ArchiveService::build_archive("264".to_string(), None, None, selected()).unwrap();
+        assert!(ArchiveService::import(&state, archive, ImportPolicy::Merge, None).await.is_err());
+
+        let cache = state.cache.read().await;
+        assert!(!cache.contains("264", "01/10/2025"));
+        assert!(cache.is_cold("264", "02/10/2025"));
+        assert!(!dir.join("cache_backup.json").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
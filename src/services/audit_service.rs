@@ -0,0 +1,202 @@
+use crate::errors::DebugAppError;
+use crate::models::{ChangeKind, ChangeLogEntry, DailyTransactions};
+use crate::services::DateService;
+use crate::state::AppState;
+use chrono::Utc;
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tracing::{info, warn};
+
+pub struct AuditService;
+
+impl AuditService {
+    fn get_audit_file_path(state: &AppState) -> String {
+        state.config().cache.audit_log_file_path.clone()
+    }
+
+    /// Bandingkan data cache lama dengan hasil fetch baru untuk satu tanggal (per `no_nota`)
+    pub fn diff_day(store: &str, date: &str, old: &DailyTransactions, new: &DailyTransactions) -> Vec<ChangeLogEntry> {
+        let detected_at = Utc::now();
+        let mut changes = Vec::new();
+
+        let entry = |kind, no_nota: &str, old, new| ChangeLogEntry {
+            detected_at,
+            store_id: Some(store.to_string()),
+            date: date.to_string(),
+            no_nota: no_nota.to_string(),
+            kind,
+            old,
+            new,
+        };
+
+        for old_tx in old.iter() {
+            match new.get(&old_tx.dedup_key()) {
+                None => changes.push(entry(ChangeKind::Removed, &old_tx.no_nota, Some(old_tx.clone()), None)),
+                Some(new_tx) => {
+                    if old_tx.total_tagihan != new_tx.total_tagihan {
+                        changes.push(entry(ChangeKind::AmountChanged, &old_tx.no_nota, Some(old_tx.clone()), Some(new_tx.clone())));
+                    }
+                    if old_tx.keterangan != new_tx.keterangan {
+                        changes.push(entry(ChangeKind::DescriptionChanged, &old_tx.no_nota, Some(old_tx.clone()), Some(new_tx.clone())));
+                    }
+                }
+            }
+        }
+
+        for new_tx in new.iter() {
+            if old.get(&new_tx.dedup_key()).is_none() {
+                changes.push(entry(ChangeKind::Added, &new_tx.no_nota, None, Some(new_tx.clone())));
+            }
+        }
+
+        changes
+    }
+
+    /// Simpan perubahan ke memory dan append ke file audit (JSON Lines)
+    pub async fn record_changes(state: &AppState, changes: Vec<ChangeLogEntry>) -> Result<(), DebugAppError> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let mut lines = String::new();
+        for change in &changes {
+            let line = serde_json::to_string(change)
+                .map_err(|e| DebugAppError::Serialization(e.to_string()))?;
+            lines.push_str(&line);
+            lines.push('\n');
+        }
+
+        info!("[AUDIT] Recorded {} changes", changes.len());
+        let max_entries = state.config().cache.audit_log_max_entries;
+        let mut audit_log = state.audit_log.write().await;
+        audit_log.extend(changes);
+        Self::truncate_oldest(&mut audit_log, max_entries);
+        drop(audit_log);
+
+        let audit_file_path = Self::get_audit_file_path(state);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&audit_file_path)
+            .await
+            .map_err(|e| DebugAppError::FileWrite(e.to_string()))?;
+        file.write_all(lines.as_bytes()).await
+            .map_err(|e| DebugAppError::FileWrite(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Load audit log dari file saat startup
+    pub async fn load_from_file(state: &AppState) -> Result<usize, DebugAppError> {
+        let audit_file_path = Self::get_audit_file_path(state);
+        let Ok(file_content) = fs::read_to_string(&audit_file_path).await else {
+            return Ok(0);
+        };
+
+        let mut entries = Vec::new();
+        for (line_no, line) in file_content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<ChangeLogEntry>(line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => warn!("[AUDIT] Skipping invalid line {} in '{}': {}", line_no + 1, audit_file_path, e),
+            }
+        }
+
+        let count = entries.len();
+        Self::truncate_oldest(&mut entries, state.config().cache.audit_log_max_entries);
+        info!("[AUDIT] Loaded {} of {} change log entries", entries.len(), count);
+        *state.audit_log.write().await = entries;
+        Ok(count)
+    }
+
+    /// Buang entri paling lama sampai tersisa `max_entries` (`AUDIT_LOG_MAX_ENTRIES`)
+    fn truncate_oldest(entries: &mut Vec<ChangeLogEntry>, max_entries: usize) {
+        if entries.len() > max_entries {
+            entries.drain(..entries.len() - max_entries);
+        }
+    }
+
+    /// Ambil perubahan satu store untuk range tanggal transaksi (opsional filter no_nota / jenis perubahan)
+    pub async fn query(
+        state: &AppState,
+        store: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+        no_nota: Option<&str>,
+        kind: Option<ChangeKind>,
+    ) -> Result<Vec<ChangeLogEntry>, DebugAppError> {
+        let parse = |label: &str, value: Option<&str>| {
+            value
+                .map(|v| DateService::parse_date(v)
+                    .map_err(|_| DebugAppError::DateParse(format!("Invalid {} date: {}", label, v))))
+                .transpose()
+        };
+        let from_date = parse("from", from)?;
+        let to_date = parse("to", to)?;
+
+        let default_store = state.config().upstream.store_id.clone();
+        let audit_log = state.audit_log.read().await;
+        let changes = audit_log
+            .iter()
+            .filter(|entry| entry.store_id.as_deref().unwrap_or(&default_store) == store)
+            .filter(|entry| {
+                let Ok(entry_date) = DateService::parse_date(&entry.date) else {
+                    return from_date.is_none() && to_date.is_none();
+                };
+                from_date.is_none_or(|d| entry_date >= d) && to_date.is_none_or(|d| entry_date <= d)
+            })
+            .filter(|entry| no_nota.is_none_or(|n| entry.no_nota == n))
+            .filter(|entry| kind.is_none_or(|k| entry.kind == k))
+            .cloned()
+            .collect();
+
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::transaksi;
+    use crate::models::Transaksi;
+
+    fn daily(rows: Vec<Transaksi>) -> DailyTransactions {
+        DailyTransactions::from_vec(rows).0
+    }
+
+    #[test]
+    fn diff_day_detects_added_removed_and_edited_rows() {
+        let old = daily(vec![transaksi("01/10/2025", "A", 100), transaksi("01/10/2025", "B", 200), transaksi("01/10/2025", "C", 300)]);
+        let new = daily(vec![transaksi("01/10/2025", "A", 100), transaksi("01/10/2025", "B", 250).paid_with("QRIS"), transaksi("01/10/2025", "D", 400)]);
+
+        let changes = AuditService::diff_day("263", "01/10/2025", &old, &new);
+        let kinds: Vec<(&str, ChangeKind)> = changes.iter().map(|c| (c.no_nota.as_str(), c.kind)).collect();
+        assert_eq!(kinds, vec![
+            ("B", ChangeKind::AmountChanged),
+            ("B", ChangeKind::DescriptionChanged),
+            ("C", ChangeKind::Removed),
+            ("D", ChangeKind::Added),
+        ]);
+        assert!(changes.iter().all(|c| c.store_id.as_deref() == Some("263") && c.date == "01/10/2025"));
+    }
+
+    #[test]
+    fn diff_day_of_identical_days_is_empty() {
+        let rows = vec![transaksi("01/10/2025", "A", 100), transaksi("01/10/2025", "B", 200)];
+        assert!(AuditService::diff_day("263", "01/10/2025", &daily(rows.clone()), &daily(rows)).is_empty());
+    }
+
+    #[test]
+    fn truncate_oldest_keeps_newest_entries() {
+        let old = daily(vec![]);
+        let new = daily((0..5).map(|i| transaksi("01/10/2025", &format!("N{}", i), 100)).collect());
+        let mut entries = AuditService::diff_day("263", "01/10/2025", &old, &new);
+
+        AuditService::truncate_oldest(&mut entries, 2);
+        assert_eq!(entries.iter().map(|e| e.no_nota.as_str()).collect::<Vec<_>>(), vec!["N3", "N4"]);
+        AuditService::truncate_oldest(&mut entries, 10);
+        assert_eq!(entries.len(), 2);
+    }
+}
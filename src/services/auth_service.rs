@@ -1,15 +1,18 @@
+use crate::config::UpstreamConfig;
 use crate::errors::DebugAppError;
+use crate::models::Payload;
+use crate::state::AppState;
 use reqwest::header::{COOKIE, SET_COOKIE};
 use scraper::{Html, Selector};
-use std::env;
-use tracing::info;
+use std::future::Future;
+use tracing::{info, info_span, instrument, warn, Instrument};
 
 pub struct AuthService;
 
 impl AuthService {
-    pub async fn perform_login(username: &str, password: &str) -> Result<String, DebugAppError> {
-        let base_url = env::var("API_BASE_URL")
-            .map_err(|_| DebugAppError::DateParse("API_BASE_URL not set".to_string()))?;
+    #[instrument(skip_all, fields(username = %username))]
+    pub async fn perform_login(upstream: &UpstreamConfig, username: &str, password: &str) -> Result<String, DebugAppError> {
+        let base_url = &upstream.api_base_url;
 
         let client = reqwest::Client::builder()
             .redirect(reqwest::redirect::Policy::none())
@@ -19,11 +22,15 @@ impl AuthService {
         let login_url = format!("{}/login", base_url);
         info!("[AUTH] Getting login page: {}", login_url);
         
-        let response = client.get(&login_url).send().await?;
-        
-        // Extract cookies before consuming response
-        let headers = response.headers().clone();
-        let html_content = response.text().await?;
+        let (headers, html_content) = async {
+            let response = client.get(&login_url).send().await?;
+            // Extract cookies before consuming response
+            let headers = response.headers().clone();
+            let html_content = response.text().await?;
+            Ok::<_, reqwest::Error>((headers, html_content))
+        }
+        .instrument(info_span!("login_page", otel.kind = "client"))
+        .await?;
 
         // Extract cookies from Set-Cookie headers
         let mut xsrf_token = String::new();
@@ -43,7 +50,7 @@ impl AuthService {
         info!("[AUTH] Extracted new_kasir_v2_session: {}",&session_token);
 
         // Step 2: Parse HTML to get _token
-        let csrf_token = {
+        let csrf_token = info_span!("parse_csrf_token").in_scope(|| {
             let document = Html::parse_document(&html_content);
             let token_selector = Selector::parse("input[name='_token']")
                 .map_err(|_| DebugAppError::Serialization("Invalid CSS selector".to_string()))?;
@@ -53,8 +60,8 @@ impl AuthService {
                 .next()
                 .and_then(|element| element.value().attr("value"))
                 .map(|s| s.to_string())
-                .ok_or_else(|| DebugAppError::Serialization("CSRF token not found".to_string()))?
-        };
+                .ok_or_else(|| DebugAppError::Serialization("CSRF token not found".to_string()))
+        })?;
 
         info!("[AUTH] Extracted CSRF token and cookies");
         info!("[AUTH] _token: {}",&csrf_token.as_str());
@@ -74,6 +81,7 @@ impl AuthService {
             .header(COOKIE, cookie_header)
             .form(&login_data)
             .send()
+            .instrument(info_span!("login_submit", otel.kind = "client"))
             .await?;
 
         // Step 4: Extract final cookies from login response
@@ -105,6 +113,63 @@ impl AuthService {
         Ok(final_cookie)
     }
 
+    /// Kredensial upstream milik server (`UPSTREAM_USERNAME` / `UPSTREAM_PASSWORD` atau akun di `upstream.accounts`)
+    fn server_credentials(upstream: &UpstreamConfig) -> Option<(&str, &str)> {
+        let username = upstream.username.as_deref()?;
+        let password = upstream.password.as_deref()?;
+        Some((username, password))
+    }
+
+    /// Login dengan kredensial server milik akun lalu simpan cookie-nya di state
+    pub async fn login_with_server_credentials(state: &AppState, account: &str) -> Result<String, DebugAppError> {
+        let config = state.config();
+        let upstream = config.upstream_for_account(account)
+            .ok_or_else(|| DebugAppError::InvalidInput(format!("Upstream account '{}' is not configured", account)))?;
+        let (username, password) = Self::server_credentials(&upstream).ok_or_else(|| {
+            DebugAppError::Unauthorized(format!(
+                "No server session and upstream account '{}' has no username (UPSTREAM_USERNAME / upstream.accounts)",
+                account
+            ))
+        })?;
+
+        let cookie = Self::perform_login(&upstream, username, password).await?;
+        state.set_session_cookie(account, Some(cookie.clone())).await;
+        state.set_unauthorized(account, false).await;
+        info!(account = %account, "[AUTH] Server session established for user: {}", username);
+        Ok(cookie)
+    }
+
+    /// Jalankan operasi smart fetch untuk range dengan cookie sesi server milik akun pemilik store
+    /// (atau cookie tersimpan di config kalau belum login). Tanggal yang sudah di-cache tidak butuh
+    /// cookie; kalau upstream menolak, login ulang sekali lalu coba lagi.
+    pub async fn with_server_session<T, F, Fut>(state: &AppState, store: &str, from: &str, to: &str, op: F) -> Result<T, DebugAppError>
+    where
+        F: Fn(Payload) -> Fut,
+        Fut: Future<Output = Result<T, DebugAppError>>,
+    {
+        let payload = |cookie: String| Payload {
+            from: from.to_string(),
+            to: to.to_string(),
+            cookie,
+        };
+
+        let config = state.config();
+        let account = config.account_for(store);
+        let cookie = match state.session_cookie(account).await {
+            Some(cookie) => cookie,
+            None => config.upstream_for(store).cookie.unwrap_or_default(),
+        };
+        match op(payload(cookie)).await {
+            Err(DebugAppError::Unauthorized(reason)) => {
+                warn!(account = %account, "[AUTH] Server session rejected ({}), logging in again", reason);
+                state.set_session_cookie(account, None).await;
+                let cookie = Self::login_with_server_credentials(state, account).await?;
+                op(payload(cookie)).await
+            }
+            other => other,
+        }
+    }
+
     fn extract_cookie_value(cookie_str: &str, cookie_name: &str) -> String {
         cookie_str
             .split(';')
@@ -0,0 +1,231 @@
+use crate::errors::DebugAppError;
+use crate::models::{CacheFile, Transaksi, CACHE_FILE_SCHEMA_VERSION};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tokio::fs;
+use tracing::info;
+
+/// Isi file cache yang sudah di-upgrade ke `CACHE_FILE_SCHEMA_VERSION`
+pub struct LoadedCacheFile {
+    pub file: CacheFile<HashMap<String, Vec<Transaksi>>>,
+    /// Versi asli file kalau file perlu dimigrasi
+    pub migrated_from: Option<u32>,
+}
+
+/// Satu langkah migrasi dari versi `from` ke `from + 1`, bekerja di JSON mentah
+/// supaya file lama tidak perlu cocok dengan struct versi sekarang
+struct Migration {
+    from: u32,
+    description: &'static str,
+    apply: fn(Value, &MigrationContext) -> Result<Value, String>,
+}
+
+struct MigrationContext<'a> {
+    store_id: &'a str,
+}
+
+/// Rantai migrasi; tambahkan langkah baru di sini setiap kali `CACHE_FILE_SCHEMA_VERSION` dinaikkan
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 1,
+    description: "wrap bare date map in a versioned envelope",
+    apply: v1_to_v2,
+}];
+
+pub struct CacheFileService;
+
+impl CacheFileService {
+    /// Baca file cache dan upgrade ke versi sekarang. `Ok(None)` kalau file belum ada.
+    /// File dari versi yang lebih baru, migrasi gagal, atau store lain = error (tidak dibuang diam-diam).
+    pub async fn read(path: &str, store_id: &str) -> Result<Option<LoadedCacheFile>, DebugAppError> {
+        let content = match fs::read_to_string(path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(DebugAppError::FileWrite(format!("Failed to read cache file {}: {}", path, e))),
+        };
+        let invalid = |message: String| DebugAppError::Serialization(format!("Invalid cache file {}: {}", path, message));
+
+        let value: Value = serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+        let (value, original_version) = Self::migrate(value, store_id).map_err(invalid)?;
+        let file: CacheFile<HashMap<String, Vec<Transaksi>>> =
+            serde_json::from_value(value).map_err(|e| invalid(e.to_string()))?;
+
+        if file.store_id != store_id {
+            return Err(DebugAppError::InvalidInput(format!(
+                "Cache file {} belongs to store '{}', this server is configured for store '{}'",
+                path, file.store_id, store_id
+            )));
+        }
+
+        Ok(Some(LoadedCacheFile {
+            file,
+            migrated_from: (original_version != CACHE_FILE_SCHEMA_VERSION).then_some(original_version),
+        }))
+    }
+
+    /// Tulis `dates` dalam envelope versi sekarang. Ditulis ke `<path>.tmp` lalu di-rename,
+    /// supaya crash di tengah penulisan tidak meninggalkan file setengah jadi.
+    pub async fn write<D: Serialize>(path: &str, store_id: &str, dates: &D) -> Result<(), DebugAppError> {
+        let file = CacheFile {
+            schema_version: CACHE_FILE_SCHEMA_VERSION,
+            created_at: Utc::now(),
+            store_id: store_id.to_string(),
+            dates,
+        };
+        let json_data = serde_json::to_string_pretty(&file)
+            .map_err(|e| DebugAppError::Serialization(e.to_string()))?;
+        let tmp_path = format!("{}.tmp", path);
+        fs::write(&tmp_path, json_data).await
+            .map_err(|e| DebugAppError::FileWrite(e.to_string()))?;
+        fs::rename(&tmp_path, path).await
+            .map_err(|e| DebugAppError::FileWrite(format!("Failed to move {} to {}: {}", tmp_path, path, e)))
+    }
+
+    /// Salin file asli sebelum ditimpa hasil migrasi, mis. `cache_backup.json.v1.bak`
+    pub async fn backup_original(path: &str, version: u32) -> Result<String, DebugAppError> {
+        let backup_path = format!("{}.v{}.bak", path, version);
+        fs::copy(path, &backup_path).await
+            .map_err(|e| DebugAppError::FileWrite(format!("Failed to back up {} to {}: {}", path, backup_path, e)))?;
+        Ok(backup_path)
+    }
+
+    /// Pindahkan file yang tidak bisa di-load ke `<path>.unreadable-<waktu>`, supaya save berikutnya
+    /// tidak menimpa history. `Ok(None)` kalau file tidak ada.
+    pub async fn quarantine(path: &str) -> Result<Option<String>, DebugAppError> {
+        if fs::metadata(path).await.is_err() {
+            return Ok(None);
+        }
+        let target = format!("{}.unreadable-{}", path, Utc::now().format("%Y%m%d%H%M%S"));
+        fs::rename(path, &target).await
+            .map_err(|e| DebugAppError::FileWrite(format!("Failed to move {} to {}: {}", path, target, e)))?;
+        Ok(Some(target))
+    }
+
+    /// Jalankan migrasi berurutan sampai versi sekarang; mengembalikan JSON baru dan versi asli
+    fn migrate(mut value: Value, store_id: &str) -> Result<(Value, u32), String> {
+        let original_version = Self::detect_version(&value)?;
+        if original_version > CACHE_FILE_SCHEMA_VERSION {
+            return Err(format!(
+                "schema version {} is newer than this build supports ({})",
+                original_version, CACHE_FILE_SCHEMA_VERSION
+            ));
+        }
+
+        let context = MigrationContext { store_id };
+        let mut version = original_version;
+        while version < CACHE_FILE_SCHEMA_VERSION {
+            let migration = MIGRATIONS
+                .iter()
+                .find(|m| m.from == version)
+                .ok_or_else(|| format!("no migration from schema version {}", version))?;
+            value = (migration.apply)(value, &context)
+                .map_err(|e| format!("migration v{} -> v{} failed: {}", version, version + 1, e))?;
+            info!(from = version, to = version + 1, "[FILE_CACHE] Migrated cache file: {}", migration.description);
+            version += 1;
+        }
+        Ok((value, original_version))
+    }
+
+    /// File tanpa `schema_version` adalah v1 (map tanggal langsung di root)
+    fn detect_version(value: &Value) -> Result<u32, String> {
+        let Value::Object(root) = value else {
+            return Err("expected a JSON object".to_string());
+        };
+        match root.get("schema_version") {
+            None => Ok(1),
+            Some(raw) => raw
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .filter(|v| *v >= 1)
+                .ok_or_else(|| format!("invalid schema_version {}", raw)),
+        }
+    }
+}
+
+/// v1 tidak menyimpan store; diasumsikan milik store yang sedang dikonfigurasi
+fn v1_to_v2(value: Value, context: &MigrationContext) -> Result<Value, String> {
+    if !value.is_object() {
+        return Err("expected a map of dates".to_string());
+    }
+    Ok(json!({
+        "schema_version": 2,
+        "created_at": Utc::now(),
+        "store_id": context.store_id,
+        "dates": value,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cache_file_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn v1_file() -> Value {
+        json!({
+            "01/10/2025": [{
+                "tanggal_transaksi": "01/10/2025",
+                "waktu_transaksi": "01/10/2025 10:00:00",
+                "keterangan": "Tunai",
+                "total_tagihan": 12500,
+                "no_nota": "INV-1"
+            }]
+        })
+    }
+
+    #[test]
+    fn chain_covers_every_version_up_to_current() {
+        for version in 1..CACHE_FILE_SCHEMA_VERSION {
+            assert!(MIGRATIONS.iter().any(|m| m.from == version), "missing migration from v{}", version);
+        }
+    }
+
+    #[test]
+    fn v1_is_wrapped_for_the_configured_store() {
+        let (value, original) = CacheFileService::migrate(v1_file(), "264").unwrap();
+        assert_eq!(original, 1);
+        assert_eq!(value["schema_version"], CACHE_FILE_SCHEMA_VERSION);
+        assert_eq!(value["store_id"], "264");
+        assert_eq!(value["dates"], v1_file());
+    }
+
+    #[test]
+    fn current_version_is_left_alone_and_bad_versions_fail() {
+        let current = json!({ "schema_version": CACHE_FILE_SCHEMA_VERSION, "store_id": "264", "dates": {} });
+        assert_eq!(CacheFileService::migrate(current.clone(), "264").unwrap(), (current, CACHE_FILE_SCHEMA_VERSION));
+
+        let newer = json!({ "schema_version": CACHE_FILE_SCHEMA_VERSION + 1 });
+        assert!(CacheFileService::migrate(newer, "264").unwrap_err().contains("newer"));
+        assert!(CacheFileService::migrate(json!({ "schema_version": 0 }), "264").is_err());
+        assert!(CacheFileService::migrate(json!({ "schema_version": "2" }), "264").is_err());
+        assert!(CacheFileService::migrate(json!([]), "264").is_err());
+    }
+
+    #[tokio::test]
+    async fn read_reports_migrated_files_and_checks_the_store() {
+        let dir = temp_dir("read");
+        let path = dir.join("cache_backup.json").to_string_lossy().to_string();
+        assert!(CacheFileService::read(&path, "264").await.unwrap().is_none());
+
+        std::fs::write(&path, v1_file().to_string()).unwrap();
+        let loaded = CacheFileService::read(&path, "264").await.unwrap().unwrap();
+        assert_eq!(loaded.migrated_from, Some(1));
+        assert_eq!(loaded.file.store_id, "264");
+        assert_eq!(loaded.file.dates["01/10/2025"][0].no_nota, "INV-1");
+
+        // Setelah ditulis ulang tidak perlu migrasi lagi, dan store lain ditolak
+        CacheFileService::write(&path, "264", &loaded.file.dates).await.unwrap();
+        let reloaded = CacheFileService::read(&path, "264").await.unwrap().unwrap();
+        assert_eq!(reloaded.migrated_from, None);
+        assert_eq!(reloaded.file.dates.len(), 1);
+        assert!(matches!(CacheFileService::read(&path, "265").await, Err(DebugAppError::InvalidInput(_))));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
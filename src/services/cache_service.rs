@@ -1,377 +1,821 @@
-#![allow(dead_code)]
-#![allow(unused_variables)]
-
+use crate::config::Config;
 use crate::errors::DebugAppError;
-use crate::models::{DebugResponse, Payload, Transaksi};
-use crate::services::{TransactionService, DateService};
-use crate::state::{AppState, JobStatus};
-use tracing::{info, warn, error};
-use tokio::time::{sleep, Duration};
+use crate::models::{ChangeLogEntry, DailyTransactions, EvictOutcome, Payload, SpillState, Transaksi};
+use crate::services::{AuditService, CacheFileService, CacheSpillService, TransactionService, DateService, MetricsService};
+use crate::state::AppState;
+use tracing::{debug, info, info_span, warn, error, Instrument};
+use tokio::sync::MutexGuard;
+use tokio::time::Instant;
 use std::collections::HashMap;
-use std::env;
-use tokio::fs;
-use serde_json;
 
 pub struct CacheService;
 
+pub struct SmartFetchResult {
+    pub data: Vec<Transaksi>,
+    pub message: String,
+}
+
+/// Hasil melengkapi cache untuk satu range: daftar key tanggal (urut) yang sudah siap dibaca
+/// dari cache, plus transaksi upstream yang tanggalnya tidak cocok dengan key mana pun atau
+/// yang tanggalnya gagal di-cache (spill tidak bisa di-load)
+pub struct SmartFill {
+    pub store: String,
+    pub dates: Vec<String>,
+    pub message: String,
+    pub uncached: Vec<Transaksi>,
+}
+
+/// Hasil `replace_range`: tanggal yang diganti dan perubahan terhadap isi cache sebelumnya
+pub struct RangeReplace {
+    pub replaced: usize,
+    pub duplicates_collapsed: usize,
+    pub empty_dates: usize,
+    pub changes: Vec<ChangeLogEntry>,
+}
+
 impl CacheService {
-    fn get_cache_file_path() -> String {
-        env::var("CACHE_FILE_PATH").unwrap_or_else(|_| "cache_backup.json".to_string())
+    /// File backup per store: store default memakai `CACHE_FILE_PATH` apa adanya, store lain
+    /// mendapat id store sebelum ekstensi (`cache_backup.json` -> `cache_backup.264.json`)
+    pub fn cache_file_path(config: &Config, store: &str) -> String {
+        let path = &config.cache.file_path;
+        if config.is_default_store(store) {
+            return path.clone();
+        }
+        let file_start = path.rfind('/').map_or(0, |i| i + 1);
+        match path[file_start..].rfind('.') {
+            Some(dot) if dot > 0 => format!("{}.{}{}", &path[..file_start + dot], store, &path[file_start + dot..]),
+            _ => format!("{}.{}", path, store),
+        }
+    }
+
+    /// Folder spill per store: store default `CACHE_SPILL_DIR`, store lain `CACHE_SPILL_DIR/store-264`
+    pub fn spill_dir(config: &Config, store: &str) -> String {
+        if config.is_default_store(store) {
+            config.cache.spill_dir.clone()
+        } else {
+            format!("{}/store-{}", config.cache.spill_dir.trim_end_matches('/'), store)
+        }
     }
 
-    // 1. Cek memory cache
-    pub async fn get_from_memory_cache(state: &AppState, date: &str) -> Option<Vec<Transaksi>> {
-        let cache_read = state.cache.read().await;
-        cache_read.get(date).cloned()
+    // 1. Cek memory cache (tanggal yang sudah dipindah ke spill di-load lagi ke memory)
+    pub async fn get_from_memory_cache(state: &AppState, store: &str, date: &str) -> Option<Vec<Transaksi>> {
+        let mut found = None;
+        let dates = [date.to_string()];
+        if let Err(e) = Self::for_each_cached(state, store, &dates, true, |_, daily| found = Some(daily.to_vec())).await {
+            error!(store = %store, date = %date, error = ?e, "[CACHE_SPILL] Failed to reload spilled date");
+        }
+        found
     }
 
-    // 2. Load ALL file cache ke memory (one-time operation)
-    pub async fn load_all_from_file_cache(state: &AppState) -> Result<usize, DebugAppError> {
-        // Clean empty entries first
-        Self::clean_empty_cache_entries(state).await?;
-        
-        let cache_file_path = Self::get_cache_file_path();
-        if let Ok(file_content) = fs::read_to_string(&cache_file_path).await {
-            if let Ok(file_cache) = serde_json::from_str::<HashMap<String, Vec<Transaksi>>>(&file_content) {
-                let count = file_cache.len();
-                let mut cache_write = state.cache.write().await;
-                
-                // Merge dengan existing cache (tidak overwrite), skip empty entries
-                for (date, transactions) in file_cache {
-                    if !transactions.is_empty() {
-                        cache_write.entry(date).or_insert(transactions);
-                    }
+    /// Panggil `f` untuk setiap tanggal di `dates` yang ada di cache, sesuai urutan `dates`.
+    /// Tanggal yang sudah dipindah ke spill dibaca dari disk; `promote = true` memasukkannya lagi ke
+    /// memory (request biasa), `false` untuk scan seluruh cache supaya tanggal yang sering dipakai tidak terdesak.
+    pub async fn for_each_cached<F>(state: &AppState, store: &str, dates: &[String], promote: bool, mut f: F) -> Result<(), DebugAppError>
+    where
+        F: FnMut(&str, &DailyTransactions),
+    {
+        let config = state.config();
+        for date in dates {
+            {
+                let cache = state.cache.read().await;
+                if let Some(daily) = cache.get(store, date) {
+                    f(date, daily);
+                    continue;
+                }
+                if !cache.is_cold(store, date) {
+                    continue;
+                }
+            }
+
+            let Some(daily) = Self::read_spilled(state, &config, store, date).await? else {
+                continue;
+            };
+            f(date, &daily);
+            if promote {
+                let promoted = state.cache.write().await.promote(store, date, daily);
+                if promoted {
+                    MetricsService::cache_spill("reload", 1);
+                    Self::enforce_memory_budget(state).await;
                 }
-                
-                info!("[FILE_CACHE] Loaded {} dates to memory cache", count);
-                return Ok(count);
             }
         }
-        Ok(0)
+        Ok(())
     }
 
-    // Clean empty entries from file cache
-    pub async fn clean_empty_cache_entries(state: &AppState) -> Result<usize, DebugAppError> {
-        let cache_file_path = Self::get_cache_file_path();
-        if let Ok(file_content) = fs::read_to_string(&cache_file_path).await {
-            if let Ok(mut file_cache) = serde_json::from_str::<HashMap<String, Vec<Transaksi>>>(&file_content) {
-                let original_count = file_cache.len();
-                
-                // Remove empty entries
-                file_cache.retain(|_, transactions| !transactions.is_empty());
-                
-                let cleaned_count = original_count - file_cache.len();
-                
-                if cleaned_count > 0 {
-                    // Save cleaned cache back to file
-                    let json_data = serde_json::to_string_pretty(&file_cache)
-                        .map_err(|e| DebugAppError::Serialization(e.to_string()))?;
-                    fs::write(&cache_file_path, json_data).await
-                        .map_err(|e| DebugAppError::FileWrite(e.to_string()))?;
-                    
-                    info!("[CACHE_CLEAN] Removed {} empty entries from backup file", cleaned_count);
+    /// Baca tanggal cold dari file spill. File yang hilang = tanggal dibuang dari cache (di-fetch lagi saat diminta).
+    async fn read_spilled(state: &AppState, config: &Config, store: &str, date: &str) -> Result<Option<DailyTransactions>, DebugAppError> {
+        match CacheSpillService::read(&Self::spill_dir(config, store), store, date).await? {
+            Some(spilled) => Ok(Some(DailyTransactions::from_vec(spilled.transactions).0)),
+            None => {
+                let mut cache = state.cache.write().await;
+                if cache.is_cold(store, date) {
+                    warn!(store = %store, date = %date, "[CACHE_SPILL] Spill file missing, dropping date from cache");
+                    cache.remove(store, date);
                 }
-                
-                return Ok(cleaned_count);
+                Ok(None)
             }
         }
-        Ok(0)
     }
 
-    // Simple check untuk single date (no file I/O)
-    pub async fn get_from_file_cache(state: &AppState, date: &str) -> Option<Vec<Transaksi>> {
-        // File cache sudah di-load ke memory saat startup, jadi cek memory saja
-        Self::get_from_memory_cache(state, date).await
+    /// Pastikan tanggal cold sudah di memory sebelum di-merge atau diganti. File spill dibaca tanpa lock
+    /// cache; guard `spill_lock` milik pemanggil menahan evict, jadi tanggal tidak kembali cold sebelum
+    /// pemanggil selesai merge. Jangan panggil `enforce_memory_budget` selama guard dipegang.
+    pub async fn ensure_hot(state: &AppState, config: &Config, store: &str, date: &str, _spill: &MutexGuard<'_, ()>) -> Result<(), DebugAppError> {
+        if !state.cache.read().await.is_cold(store, date) {
+            return Ok(());
+        }
+        let Some(daily) = Self::read_spilled(state, config, store, date).await? else {
+            return Ok(());
+        };
+        // Hanya dipasang kalau masih cold; kalau sudah di-load atau dihapus request lain, hasil baca dibuang
+        if state.cache.write().await.promote(store, date, daily) {
+            MetricsService::cache_spill("reload", 1);
+        }
+        Ok(())
     }
 
-    // 3. Get missing dates yang perlu di-request
-    pub async fn get_missing_dates(state: &AppState, dates: &[String]) -> Vec<String> {
-        let mut missing_dates = Vec::new();
-        
-        for date in dates {
-            // Cek apakah data benar-benar ada dan tidak kosong
-            match Self::get_from_memory_cache(state, date).await {
-                None => missing_dates.push(date.clone()), // Tidak ada data
-                Some(transactions) if transactions.is_empty() => missing_dates.push(date.clone()), // Ada tapi kosong
-                Some(_) => {} // Ada data yang valid
-            }
+    /// Hapus tanggal dari memory, lalu file spill-nya tanpa lock cache. Guard `spill_lock` milik pemanggil
+    /// menahan evict, jadi file spill tanggal ini tidak sedang ditulis saat dihapus.
+    pub async fn remove_date(state: &AppState, config: &Config, store: &str, date: &str, _spill: &MutexGuard<'_, ()>) -> bool {
+        if !state.cache.write().await.remove(store, date) {
+            return false;
         }
-        
-        missing_dates
+        if let Err(e) = CacheSpillService::remove(&Self::spill_dir(config, store), date).await {
+            warn!(store = %store, date = %date, error = ?e, "[CACHE_SPILL] Failed to remove spill file");
+        }
+        true
     }
 
-    // Save cache to file
-    pub async fn save_cache_to_file(state: &AppState) -> Result<(), DebugAppError> {
-        let cache_read = state.cache.read().await;
-        let json_data = serde_json::to_string_pretty(&*cache_read)
-            .map_err(|e| DebugAppError::Serialization(e.to_string()))?;
-        
-        let cache_file_path = Self::get_cache_file_path();
-        fs::write(&cache_file_path, json_data).await
-            .map_err(|e| DebugAppError::FileWrite(e.to_string()))?;
-        
-        info!("[FILE_CACHE] Saved {} dates to backup file", cache_read.len());
-        Ok(())
+    /// Ganti isi cache setiap tanggal di `dates` dengan baris hasil fetch ulang (`rows`), jadi transaksi
+    /// yang di-void kasir ikut hilang. Perubahan dihitung terhadap isi cache sebelumnya, sehingga refresh
+    /// berikutnya tidak mencatat void yang sama lagi. Tanggal tanpa baris di `rows` dibiarkan (balasan
+    /// kosong bisa karena upstream sedang bermasalah), begitu juga tanggal yang spill-nya gagal di-load.
+    pub async fn replace_range(state: &AppState, store: &str, dates: Vec<String>, rows: &[Transaksi]) -> RangeReplace {
+        let config = state.config();
+        let mut result = RangeReplace { replaced: 0, duplicates_collapsed: 0, empty_dates: 0, changes: Vec::new() };
+        {
+            // Tahan evict selama refresh; file spill dibaca tanpa lock cache (`ensure_hot`)
+            let spill = state.spill_lock.lock().await;
+            for date in dates {
+                let date_data: Vec<_> = rows.iter()
+                    .filter(|t| DateService::normalize_date_for_api(&t.tanggal_transaksi).is_ok_and(|normalized| normalized == date))
+                    .cloned()
+                    .collect();
+                if date_data.is_empty() {
+                    result.empty_dates += 1;
+                    continue;
+                }
+                let (fresh, collapsed) = DailyTransactions::from_vec(date_data);
+                result.duplicates_collapsed += collapsed;
+
+                if let Err(e) = Self::ensure_hot(state, &config, store, &date, &spill).await {
+                    error!(store = %store, date = %date, error = ?e, "[FORCE_REFRESH] Cannot reload spilled date, skipped");
+                    continue;
+                }
+                let mut cache = state.cache.write().await;
+                if let Some(old) = cache.peek(store, &date) {
+                    result.changes.extend(AuditService::diff_day(store, &date, old, &fresh));
+                }
+                cache.insert(store, date, fresh);
+                result.replaced += 1;
+            }
+        }
+        Self::enforce_memory_budget(state).await;
+        result
     }
-    pub async fn is_date_cached(state: &AppState, date: &str) -> bool {
-        // Setelah startup, semua file cache sudah di-load ke memory
-        // Jadi cukup cek memory cache saja
-        Self::get_from_memory_cache(state, date).await.is_some()
+
+    /// Pindahkan tanggal yang paling lama tidak diakses (dari store mana pun) ke spill sampai memory
+    /// cache di bawah `CACHE_MEMORY_BUDGET_MB`. Gagal tulis spill = tanggal tetap di memory dan dicoba lagi nanti.
+    pub async fn enforce_memory_budget(state: &AppState) -> usize {
+        let config = state.config();
+        if config.cache.memory_budget_mb == 0 {
+            return 0;
+        }
+        let budget_bytes = config.cache.memory_budget_mb * 1024 * 1024;
+        if state.cache.read().await.hot_bytes() <= budget_bytes {
+            return 0;
+        }
+
+        // Satu evict dalam satu waktu: file spill ditulis tanpa lock cache, jadi dua evict tanggal yang
+        // sama tidak boleh menulis file yang sama bersamaan
+        let _spill = state.spill_lock.lock().await;
+        let victims = state.cache.read().await.lru_victims(budget_bytes);
+        let mut evicted = 0;
+        for (store, date) in victims {
+            // Salin satu tanggal saja per putaran supaya memory tambahan selama tulis tetap kecil
+            let Some(candidate) = state.cache.read().await.spill_candidate(&store, &date) else {
+                continue;
+            };
+            let spill_dir = Self::spill_dir(&config, &store);
+            if let Some(daily) = &candidate.daily {
+                if let Err(e) = CacheSpillService::write(&spill_dir, &store, &date, daily).await {
+                    error!(store = %store, date = %date, error = ?e, "[CACHE_SPILL] Failed to write spill file, keeping date in memory");
+                    break;
+                }
+            }
+
+            let deleted = {
+                let mut cache = state.cache.write().await;
+                match cache.evict_if_unchanged(&store, &date, candidate.version) {
+                    EvictOutcome::Evicted => {
+                        evicted += 1;
+                        false
+                    }
+                    EvictOutcome::Changed => {
+                        debug!(store = %store, date = %date, "[CACHE_SPILL] Date changed while spilling, keeping it in memory");
+                        false
+                    }
+                    EvictOutcome::Gone => candidate.daily.is_some() && !cache.contains(&store, &date),
+                }
+            };
+            // Dihapus selagi file ditulis; buang file-nya setelah lock cache dilepas (`spill_lock` masih dipegang)
+            if deleted {
+                if let Err(e) = CacheSpillService::remove(&spill_dir, &date).await {
+                    warn!(store = %store, date = %date, error = ?e, "[CACHE_SPILL] Failed to remove spill file of deleted date");
+                }
+            }
+        }
+
+        if evicted > 0 {
+            let cache = state.cache.read().await;
+            MetricsService::cache_spill("evict", evicted);
+            info!(
+                evicted,
+                memory_dates = cache.hot_len(),
+                memory_bytes = cache.hot_bytes(),
+                "[CACHE_SPILL] Moved least recently used dates to disk"
+            );
+        }
+        evicted
     }
 
-    pub async fn is_date_processing(state: &AppState, date: &str) -> bool {
-        let processing_read = state.processing.read().await;
-        *processing_read.get(date).unwrap_or(&false)
+    // 2. Load file cache satu store ke memory (one-time operation), tanggal di folder spill dicatat sebagai cold
+    pub async fn load_store_from_file_cache(state: &AppState, store: &str) -> Result<usize, DebugAppError> {
+        Self::load_store(state, store, false).await
     }
 
-    pub async fn get_cached_transactions_for_date(state: &AppState, date: &str) -> Option<Vec<Transaksi>> {
-        // Setelah startup, semua data sudah di-load ke memory
-        Self::get_from_memory_cache(state, date).await
+    /// Load untuk `cache export` di CLI: tidak menulis apa pun ke disk (file rusak tidak dipindah,
+    /// file hasil migrasi tidak ditulis ulang, tidak spill) dan gagal pada error pertama
+    pub async fn load_store_read_only(state: &AppState, store: &str) -> Result<usize, DebugAppError> {
+        Self::load_store(state, store, true).await
     }
 
-    pub async fn set_date_processing(state: &AppState, date: &str, processing: bool) {
-        let mut processing_write = state.processing.write().await;
-        if processing {
-            processing_write.insert(date.to_string(), true);
-        } else {
-            processing_write.remove(date);
+    async fn load_store(state: &AppState, store: &str, read_only: bool) -> Result<usize, DebugAppError> {
+        // File tidak ada = start kosong; file ada tapi tidak bisa dibaca/di-migrasi = error (readiness degraded)
+        let config = state.config();
+        let strict = config.cache.strict_load || read_only;
+        let cache_file_path = Self::cache_file_path(&config, store);
+        let spill_dir = Self::spill_dir(&config, store);
+        let (mut file_dates, file_created_at, migrated_from) = match CacheFileService::read(&cache_file_path, store).await? {
+            Some(loaded) => (loaded.file.dates, Some(loaded.file.created_at), loaded.migrated_from),
+            None => (HashMap::new(), None, None),
+        };
+
+        // Tanggal yang ada di file backup dan di spill: data terbaru (created_at) di-merge di atas data
+        // yang lebih lama, spill jadi stale. `older` menyimpan data yang kalah.
+        let mut older: HashMap<String, Vec<Transaksi>> = HashMap::new();
+        let mut spilled = 0;
+        for date in Self::list_spill_dir(strict, &spill_dir).await? {
+            let spill = match CacheSpillService::read(&spill_dir, store, &date).await {
+                Ok(Some(spill)) => spill,
+                Ok(None) => continue,
+                Err(e) => {
+                    Self::reject_spill_file(strict, &spill_dir, &date, e).await?;
+                    continue;
+                }
+            };
+            match file_dates.remove(&date) {
+                Some(in_file) => {
+                    let spill_is_newer = file_created_at.is_none_or(|created_at| spill.created_at > created_at);
+                    let (newer, loser) = if spill_is_newer {
+                        (spill.transactions, in_file)
+                    } else {
+                        (in_file, spill.transactions)
+                    };
+                    file_dates.insert(date.clone(), newer);
+                    older.insert(date, loser);
+                }
+                None => {
+                    state.cache.write().await.insert_cold(store, date, spill.transactions.len());
+                    spilled += 1;
+                }
+            }
+        }
+
+        let mut duplicates_collapsed = 0;
+        let mut empty_dates = 0;
+        {
+            // Urut tanggal, supaya tanggal terbaru dianggap paling baru diakses dan paling akhir di-evict
+            let mut dates: Vec<(String, Vec<Transaksi>)> = file_dates.into_iter().collect();
+            dates.sort_by_key(|(date, _)| DateService::parse_date(date).ok());
+
+            let mut cache_write = state.cache.write().await;
+            // Merge dengan existing cache (tidak overwrite), skip empty entries
+            for (date, transactions) in dates {
+                let loser = older.remove(&date);
+                if transactions.is_empty() && loser.as_ref().is_none_or(Vec::is_empty) {
+                    empty_dates += 1;
+                    continue;
+                }
+                if cache_write.is_hot(store, &date) {
+                    continue;
+                }
+                let (daily, spill) = match loser {
+                    Some(loser) => {
+                        let (mut daily, _) = DailyTransactions::from_vec(loser);
+                        duplicates_collapsed += daily.merge(transactions).duplicates_collapsed;
+                        (daily, SpillState::Stale)
+                    }
+                    None => {
+                        let (daily, collapsed) = DailyTransactions::from_vec(transactions);
+                        duplicates_collapsed += collapsed;
+                        (daily, SpillState::None)
+                    }
+                };
+                cache_write.insert_with_spill(store, date, daily, spill);
+            }
+        }
+
+        if duplicates_collapsed > 0 {
+            info!(store = %store, "[FILE_CACHE] Collapsed {} duplicate transactions from backup file", duplicates_collapsed);
+        }
+        if empty_dates > 0 {
+            info!(store = %store, "[FILE_CACHE] Skipped {} empty dates from backup file", empty_dates);
+        }
+        let count = state.cache.read().await.store_len(store);
+        info!(store = %store, spilled, "[FILE_CACHE] Loaded {} dates to memory cache", count - spilled);
+        if read_only {
+            if let Some(version) = migrated_from {
+                info!(store = %store, from = version, "[FILE_CACHE] Cache file migrated in memory only (read-only load)");
+            }
+            return Ok(count);
         }
+        Self::enforce_memory_budget(state).await;
+
+        // File lama disimpan dulu, baru ditulis ulang dalam format sekarang
+        if let Some(version) = migrated_from {
+            let backup_path = CacheFileService::backup_original(&cache_file_path, version).await?;
+            Self::write_store_file(state, &config, store).await?;
+            info!(store = %store, from = version, backup = %backup_path, "[FILE_CACHE] Upgraded cache file to the current schema");
+        }
+        Ok(count)
     }
 
-    pub async fn cache_transactions_for_date(state: &AppState, date: &str, transactions: Vec<Transaksi>) {
-        // Save to memory cache only - file save akan dilakukan batch
-        let mut cache_write = state.cache.write().await;
-        cache_write.insert(date.to_string(), transactions);
-        drop(cache_write);
-        
-        info!("[CACHE] Data disimpan ke memory untuk tanggal: {}", date);
+    /// Folder spill yang tidak bisa dibaca hanya menghentikan load kalau `strict` (`CACHE_STRICT_LOAD` / CLI)
+    async fn list_spill_dir(strict: bool, spill_dir: &str) -> Result<Vec<String>, DebugAppError> {
+        match CacheSpillService::list(spill_dir).await {
+            Ok(dates) => Ok(dates),
+            Err(e) if !strict => {
+                warn!(error = ?e, "[CACHE_SPILL] Cannot read spill dir, starting without spilled dates");
+                Ok(Vec::new())
+            }
+            Err(e) => Err(e),
+        }
     }
 
-    // Batch save untuk efisiensi dan mencegah race condition
-    pub async fn save_cache_batch(state: &AppState) -> Result<(), DebugAppError> {
-        Self::save_cache_to_file(state).await
+    /// File spill rusak/store lain: error kalau `strict`, kalau tidak dipindah ke samping seperti file backup
+    async fn reject_spill_file(strict: bool, spill_dir: &str, date: &str, e: DebugAppError) -> Result<(), DebugAppError> {
+        if strict {
+            return Err(e);
+        }
+        let moved_to = CacheFileService::quarantine(&CacheSpillService::path(spill_dir, date)?).await?;
+        warn!(date = %date, moved_to = ?moved_to, "[CACHE_SPILL] Skipping unreadable spill file: {}", e.message());
+        Ok(())
     }
 
-    pub async fn get_date_range_transactions(state: &AppState, payload: &Payload) -> Result<Vec<Transaksi>, DebugAppError> {
-        let dates = DateService::get_date_range(&payload.from, &payload.to)
-            .map_err(|_| DebugAppError::DateParse("Invalid date format".to_string()))?;
-        
-        let mut all_transactions = Vec::new();
-        let mut missing_dates = Vec::new();
-        
-        // Check cache first
-        for date in &dates {
-            if let Some(transactions) = Self::get_cached_transactions_for_date(state, date).await {
-                all_transactions.extend(transactions);
-            } else {
-                missing_dates.push(date.clone());
-            }
+    // Clean empty entries from file cache
+    pub async fn clean_empty_cache_entries(state: &AppState, store: &str) -> Result<usize, DebugAppError> {
+        let cache_file_path = Self::cache_file_path(&state.config(), store);
+        let Some(loaded) = CacheFileService::read(&cache_file_path, store).await? else {
+            return Ok(0);
+        };
+
+        let mut file_cache = loaded.file.dates;
+        let original_count = file_cache.len();
+        file_cache.retain(|_, transactions| !transactions.is_empty());
+        let cleaned_count = original_count - file_cache.len();
+
+        if cleaned_count > 0 {
+            CacheFileService::write(&cache_file_path, store, &file_cache).await?;
+            info!(store = %store, "[CACHE_CLEAN] Removed {} empty entries from backup file", cleaned_count);
         }
-        
-        // If no cached data found, return error to trigger fetch
-        if all_transactions.is_empty() && !missing_dates.is_empty() {
-            return Err(DebugAppError::DateParse("No cached data found".to_string()));
+        Ok(cleaned_count)
+    }
+
+    // Save cache to file
+    pub async fn save_cache_to_file(state: &AppState) -> Result<(), DebugAppError> {
+        let started = Instant::now();
+        let result = Self::write_cache_file(state)
+            .instrument(info_span!("save_cache_to_file"))
+            .await;
+        let elapsed = started.elapsed();
+        MetricsService::observe_persist(result.is_ok(), elapsed);
+        if let Err(e) = &result {
+            error!(elapsed_ms = elapsed.as_millis() as u64, error = ?e, "[FILE_CACHE] Failed to save backup file");
         }
-        
-        Ok(all_transactions)
+        state.record_persist(result.as_ref().err().map(DebugAppError::message)).await;
+        result
     }
 
-    pub async fn get_date_range_data(state: &AppState, payload: &Payload) -> Result<DebugResponse, DebugAppError> {
-        let dates = DateService::get_date_range(&payload.from, &payload.to)
-            .map_err(|_| DebugAppError::DateParse("Invalid date format".to_string()))?;
-        
-        let mut all_transactions = Vec::new();
-        let mut missing_dates = Vec::new();
-        
-        for date in &dates {
-            if let Some(transactions) = Self::get_cached_transactions_for_date(state, date).await {
-                all_transactions.extend(transactions);
-                info!("[CACHE] Cache hit untuk tanggal: {}", date);
-            } else {
-                missing_dates.push(date.clone());
-                info!("[CACHE] Cache miss untuk tanggal: {}", date);
+    /// Tulis file backup setiap store yang ada di cache (store default selalu). Store yang gagal
+    /// tidak menghentikan store lain; error pertama dikembalikan.
+    async fn write_cache_file(state: &AppState) -> Result<(), DebugAppError> {
+        let config = state.config();
+        let mut stores = vec![config.upstream.store_id.clone()];
+        for store in state.cache.read().await.stores() {
+            if !stores.contains(store) {
+                stores.push(store.clone());
             }
         }
-        
-        if !missing_dates.is_empty() {
-            info!("[CACHE] Fetching {} missing dates", missing_dates.len());
-            
-            for date in missing_dates {
-                if Self::is_date_processing(state, &date).await {
-                    info!("[CACHE] Tanggal {} sedang diproses, skip", date);
-                    continue;
+
+        let mut first_error = None;
+        for store in stores {
+            if let Err(e) = Self::write_store_file(state, &config, &store).await {
+                warn!(store = %store, error = ?e, "[FILE_CACHE] Backup file for store not saved");
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// File backup store berisi tanggal yang ada di memory; tanggal di spill sudah tersimpan di file masing-masing
+    async fn write_store_file(state: &AppState, config: &Config, store: &str) -> Result<(), DebugAppError> {
+        let cache_file_path = Self::cache_file_path(config, store);
+        let spill_dir = Self::spill_dir(config, store);
+        // Snapshot dulu, serialize dan tulis setelah lock dilepas
+        let (hot_dates, stale, evictions) = {
+            let cache_read = state.cache.read().await;
+            (cache_read.hot_dates(store), cache_read.stale_spills(store), cache_read.eviction_count())
+        };
+        let dates = hot_dates.len();
+        CacheFileService::write(&cache_file_path, store, &hot_dates).await?;
+        drop(hot_dates);
+
+        // Spill stale sekarang sudah tercakup file backup. Kalau ada evict sejak snapshot, spill bisa jadi
+        // lebih baru dari file backup, jadi dibersihkan di save berikutnya saja. `spill_lock` menahan evict
+        // selama file dihapus, jadi yang masih stale setelah dicek tidak bisa ditulis ulang di tengah jalan.
+        if !stale.is_empty() {
+            let _spill = state.spill_lock.lock().await;
+            let stale: Vec<String> = {
+                let cache = state.cache.read().await;
+                if cache.eviction_count() != evictions {
+                    Vec::new()
+                } else {
+                    stale.into_iter().filter(|date| cache.spill_state(store, date) == Some(SpillState::Stale)).collect()
                 }
-                
-                Self::set_date_processing(state, &date, true).await;
-                
-                let single_date_payload = Payload {
-                    from: date.to_string(),
-                    to: date.to_string(),
-                    cookie: payload.cookie.clone(),
-                };
-                
-                match TransactionService::fetch_all_pages(&single_date_payload).await {
-                    Ok(response) => {
-                        // Only cache if data is not empty
-                        if !response.data.is_empty() {
-                            Self::cache_transactions_for_date(state, &date, response.data.clone()).await;
-                        }
-                        all_transactions.extend(response.data);
-                    }
-                    Err(e) => {
-                        error!("[CACHE] Error fetching data untuk tanggal {}: {:?}", &date, e);
-                    }
+            };
+            let mut removed = Vec::new();
+            for date in stale {
+                match CacheSpillService::remove(&spill_dir, &date).await {
+                    Ok(()) => removed.push(date),
+                    Err(e) => warn!(store = %store, date = %date, error = ?e, "[CACHE_SPILL] Failed to remove stale spill file"),
+                }
+            }
+            if !removed.is_empty() {
+                let mut cache = state.cache.write().await;
+                for date in removed {
+                    cache.clear_spill(store, &date);
                 }
-                
-                // PENTING: Selalu reset processing flag, bahkan jika error
-                Self::set_date_processing(state, &date, false).await;
             }
         }
-        
-        Ok(DebugResponse {
-            total_transaksi: all_transactions.len(),
-            data: all_transactions,
+
+        info!(store = %store, dates, "[FILE_CACHE] Saved backup file");
+        Ok(())
+    }
+    /// Ambil data dari cache saja (tanpa fetch), urut per tanggal.
+    /// Tanpa `from`/`to` = seluruh cache (tanggal di spill dibaca tanpa dimasukkan lagi ke memory).
+    pub async fn collect_cached(state: &AppState, store: &str, from: Option<&str>, to: Option<&str>) -> Result<Vec<Transaksi>, DebugAppError> {
+        let dates = Self::dates_in_range(state, store, from, to).await?;
+
+        let mut transactions = Vec::new();
+        Self::for_each_cached(state, store, &dates, from.is_some(), |_, daily| transactions.extend(daily.iter().cloned())).await?;
+        Ok(transactions)
+    }
+
+    /// Key tanggal untuk range `from`/`to`, atau semua tanggal store di cache (urut tanggal) kalau keduanya kosong
+    pub async fn dates_in_range(state: &AppState, store: &str, from: Option<&str>, to: Option<&str>) -> Result<Vec<String>, DebugAppError> {
+        match (from, to) {
+            (Some(from), Some(to)) => DateService::get_date_range(from, to)
+                .map_err(|_| DebugAppError::DateParse("Invalid date format".to_string())),
+            (None, None) => {
+                let mut keys = state.cache.read().await.dates(store);
+                keys.sort_by_key(|k| DateService::parse_date(k).ok());
+                Ok(keys)
+            }
+            _ => Err(DebugAppError::InvalidInput("Both from and to are required for a range".to_string())),
+        }
+    }
+
+    /// Cari nota di seluruh cache (tanpa request ke upstream), urut tanggal.
+    /// Tanggal di spill dibaca dari disk satu per satu.
+    pub async fn find_by_nota(state: &AppState, store: &str, no_nota: &str) -> Result<Vec<Transaksi>, DebugAppError> {
+        let dates = Self::dates_in_range(state, store, None, None).await?;
+
+        let mut found = Vec::new();
+        Self::for_each_cached(state, store, &dates, false, |_, daily| found.extend(daily.get(no_nota).cloned())).await?;
+        Ok(found)
+    }
+
+    /// Ambil data range dari memory cache, tanggal yang belum ada di-fetch dari upstream
+    /// (tanggal berurutan digabung jadi satu range) lalu di-merge ke cache
+    pub async fn get_range_with_smart_fetch(state: &AppState, store: &str, payload: &Payload) -> Result<SmartFetchResult, DebugAppError> {
+        let fill = Self::fill_missing_dates(state, store, payload).await?;
+
+        let mut data = Vec::new();
+        Self::for_each_cached(state, store, &fill.dates, true, |_, daily| data.extend(daily.iter().cloned())).await?;
+        data.extend(fill.uncached);
+
+        Ok(SmartFetchResult {
+            data,
+            message: fill.message,
         })
     }
 
-    pub async fn fetch_and_cache_date_range_background(payload: Payload, state: AppState, job_id: String) -> Result<(), DebugAppError> {
-        let dates = DateService::get_date_range(&payload.from, &payload.to)
+    /// Lengkapi cache untuk range payload tanpa mengumpulkan datanya. Tanggal yang belum ada
+    /// di-fetch dari upstream dan di-merge ke cache; pemanggil membaca cache per tanggal.
+    pub async fn fill_missing_dates(state: &AppState, store: &str, payload: &Payload) -> Result<SmartFill, DebugAppError> {
+        // Normalize dates to DD/MM/YYYY format first
+        let from_normalized = DateService::normalize_date_for_api(&payload.from)
+            .map_err(|_| DebugAppError::DateParse("Invalid from date format".to_string()))?;
+        let to_normalized = DateService::normalize_date_for_api(&payload.to)
+            .map_err(|_| DebugAppError::DateParse("Invalid to date format".to_string()))?;
+
+        info!(store = %store, from = %from_normalized, to = %to_normalized, "[CACHE_CHECK] Range requested");
+
+        // Get date range using normalized dates
+        let dates = DateService::get_date_range(&from_normalized, &to_normalized)
             .map_err(|_| DebugAppError::DateParse("Invalid date format".to_string()))?;
-        
-        // OPTIMASI: Hanya ambil tanggal yang benar-benar missing
-        let missing_dates = Self::get_missing_dates(&state, &dates).await;
-        
-        if missing_dates.is_empty() {
-            info!("[JOB:{}] Semua data sudah tersedia di cache, tidak perlu request", job_id);
-            return Ok(());
-        }
-        
-        info!("[JOB:{}] Perlu fetch {} dari {} tanggal", job_id, missing_dates.len(), dates.len());
-        
-        let batch_size: usize = env::var("BATCH_SIZE")
-            .unwrap_or_else(|_| "5".to_string())
-            .parse().unwrap_or(5);
-        let max_memory_mb: usize = env::var("MAX_MEMORY_MB")
-            .unwrap_or_else(|_| "50".to_string())
-            .parse().unwrap_or(50);
-        
-        for batch in missing_dates.chunks(batch_size) {
-            let mut batch_memory_usage = 0;
-            
-            for date in batch {
-                // Check if job was cancelled
-                if let Some(job_status) = state.get_job_status(&job_id).await {
-                    if !matches!(job_status, JobStatus::Running) {
-                        info!("[JOB:{}] Job cancelled, stopping", job_id);
-                        return Ok(());
-                    }
-                }
-                
-                // Double-check cache (might be filled by another job)
-                if Self::is_date_cached(&state, date).await {
-                    info!("[JOB:{}] Tanggal {} sudah di-cache oleh job lain, skip", job_id, date);
-                    continue;
-                }
-                
-                // Atomic check-and-set untuk mencegah race condition
-                {
-                    let mut processing_write = state.processing.write().await;
-                    if processing_write.contains_key(date) {
-                        info!("[JOB:{}] Tanggal {} sedang diproses job lain, skip", job_id, date);
-                        continue;
-                    }
-                    processing_write.insert(date.to_string(), true);
-                }
-                
-                let single_date_payload = Payload {
-                    from: date.to_string(),
-                    to: date.to_string(),
-                    cookie: payload.cookie.clone(),
-                };
-                
-                match Self::fetch_with_retry(&single_date_payload, 3).await {
-                    Ok(response) => {
-                        let estimated_size = response.data.len() * 200;
-                        batch_memory_usage += estimated_size;
-                        
-                        // Only cache if data is not empty
-                        if !response.data.is_empty() {
-                            Self::cache_transactions_for_date(&state, date, response.data).await;
-                            info!("[JOB:{}] Berhasil fetch dan cache tanggal {}", job_id, date);
-                        } else {
-                            info!("[JOB:{}] Tanggal {} kosong, tidak di-cache", job_id, date);
-                        }
-                        
-                        if batch_memory_usage > max_memory_mb * 1024 * 1024 {
-                            info!("[JOB:{}] Memory limit reached, processing batch", job_id);
-                            break;
-                        }
-                    }
-                    Err(e) => {
-                        error!("[JOB:{}] Failed fetch tanggal {} after retries: {:?}", job_id, date, e);
-                        
-                        // If unauthorized, set state and stop entire job
-                        if matches!(e, DebugAppError::Unauthorized(_)) {
-                            error!("[JOB:{}] Unauthorized - stopping entire job", job_id);
-                            state.set_unauthorized(true).await;
-                            return Err(e);
-                        }
-                    }
-                }
-                
-                // Reset processing flag dengan proper error handling
-                {
-                    let mut processing_write = state.processing.write().await;
-                    processing_write.remove(date);
+
+        info!(dates = dates.len(), "[CACHE_CHECK] Checking cache");
+
+        // Check cache and find missing dates
+        let mut missing_dates = Vec::new();
+        {
+            let cache = state.cache.read().await;
+            for date in &dates {
+                if let Some(transactions) = cache.transaction_count(store, date) {
+                    MetricsService::cache_lookup(true);
+                    info!(date = %date, transactions, "[CACHE_HIT] Date served from cache");
+                } else {
+                    MetricsService::cache_lookup(false);
+                    info!(date = %date, "[CACHE_MISS] Date not cached");
+                    missing_dates.push(date.clone());
                 }
             }
-            
-            // Save cache to file setelah setiap batch (bukan setiap tanggal)
-            if let Err(e) = Self::save_cache_batch(&state).await {
-                warn!("[JOB:{}] Failed to save cache batch: {:?}", job_id, e);
-            }
-            
-            // Delay between batches
-            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
         }
-        
-        // Final save setelah job selesai
-        if let Err(e) = Self::save_cache_batch(&state).await {
-            warn!("[JOB:{}] Failed to save final cache: {:?}", job_id, e);
+
+        // If all cached, return immediately
+        if missing_dates.is_empty() {
+            state.set_unauthorized(state.config().account_for(store), false).await;
+            return Ok(SmartFill {
+                store: store.to_string(),
+                dates,
+                message: "All data from cache".to_string(),
+                uncached: Vec::new(),
+            });
         }
-        
-        info!("[JOB:{}] Selesai processing {} missing dates", job_id, missing_dates.len());
-        Ok(())
+
+        // Tercatat sebagai job (readiness, drain shutdown), dibatasi MAX_CONCURRENT_JOBS dan JOB_TIMEOUT_SECS
+        state
+            .run_job(store, payload, Self::fetch_missing_ranges(state, store, payload, dates, missing_dates))
+            .await
     }
 
-    async fn fetch_with_retry(payload: &Payload, max_retries: u32) -> Result<DebugResponse, DebugAppError> {
-        let mut last_error = None;
+    /// Fetch tanggal yang belum ada (tanggal berurutan digabung jadi satu range) lalu merge ke cache.
+    /// Saat server shutdown, range berikutnya tidak di-fetch lagi.
+    async fn fetch_missing_ranges(
+        state: &AppState,
+        store: &str,
+        payload: &Payload,
+        mut dates: Vec<String>,
+        missing_dates: Vec<String>,
+    ) -> Result<SmartFill, DebugAppError> {
+        info!(missing = missing_dates.len(), "[SMART_FETCH] Fetching missing dates");
+
+        // Group consecutive missing dates into ranges
+        let mut ranges: Vec<(String, String)> = Vec::new();
+        let mut range_start = missing_dates[0].clone();
+        let mut range_end = missing_dates[0].clone();
+
+        for i in 1..missing_dates.len() {
+            let prev_date = DateService::parse_date(&missing_dates[i - 1]).unwrap();
+            let curr_date = DateService::parse_date(&missing_dates[i]).unwrap();
         
-        for attempt in 1..=max_retries {
-            match TransactionService::fetch_all_pages(payload).await {
+            if (curr_date - prev_date).num_days() == 1 {
+                range_end = missing_dates[i].clone();
+            } else {
+                ranges.push((range_start.clone(), range_end.clone()));
+                range_start = missing_dates[i].clone();
+                range_end = missing_dates[i].clone();
+            }
+        }
+        ranges.push((range_start, range_end));
+
+        let ranges_count = ranges.len();
+        info!(ranges = ranges_count, "[SMART_FETCH] Grouped missing dates into ranges");
+
+        // Fetch each range with 2-step pagination
+        let mut uncached = Vec::new();
+        let mut not_cached = Vec::new();
+        for (from, to) in ranges {
+            if state.is_shutting_down() {
+                warn!(store = %store, from = %from, "[SMART_FETCH] Server shutting down, skipping remaining ranges");
+                break;
+            }
+            let range_payload = Payload {
+                from: from.clone(),
+                to: to.clone(),
+                cookie: payload.cookie.clone(),
+            };
+
+            match TransactionService::fetch_direct_two_loops(&state.config().upstream_for(store), &range_payload).await {
                 Ok(response) => {
-                    if attempt > 1 {
-                        info!("[RETRY] Success on attempt {} for {}", attempt, payload.from);
+                    state.record_upstream_success().await;
+                    // Only cache if data is not empty
+                    if !response.data.is_empty() {
+                        // Debug: Log sample transaction date format
+                        if let Some(first_tx) = response.data.first() {
+                            info!("[DEBUG] Sample transaction date from API: '{}'", first_tx.tanggal_transaksi);
+                        }
+                    
+                        let range_dates = DateService::get_date_range(&from, &to).unwrap();
+                        info!("[DEBUG] Expected cache key format (first 3): {:?}", range_dates.iter().take(3).collect::<Vec<_>>());
+                    
+                        let config = state.config();
+                        // Tahan evict selama merge; file spill dibaca tanpa lock cache (`ensure_hot`)
+                        let spill = state.spill_lock.lock().await;
+                        let mut total_cached = 0;
+                        for date in range_dates {
+                            let date_data: Vec<_> = response.data.iter()
+                                .filter(|t| {
+                                    // Normalize transaction date to DD/MM/YYYY for comparison
+                                    if let Ok(normalized) = DateService::normalize_date_for_api(&t.tanggal_transaksi) {
+                                        normalized == date
+                                    } else {
+                                        false
+                                    }
+                                })
+                                .cloned()
+                                .collect();
+                            if !date_data.is_empty() {
+                                info!("[CACHE_INSERT] Merging {} transactions for key '{}'", date_data.len(), date);
+                                if let Err(e) = Self::ensure_hot(state, &config, store, &date, &spill).await {
+                                    // Spill lama tidak boleh tertimpa; data hasil fetch tetap dikirim ke client
+                                    error!(store = %store, date = %date, error = ?e, "[CACHE_SPILL] Cannot reload spilled date, returning fetched data uncached");
+                                    uncached.extend(date_data);
+                                    not_cached.push(date);
+                                    continue;
+                                }
+                                let stats = state.cache.write().await.merge(store, &date, date_data);
+                                if stats.duplicates_collapsed > 0 {
+                                    info!("[CACHE_INSERT] Collapsed {} duplicate transactions", stats.duplicates_collapsed);
+                                }
+                                total_cached += 1;
+                            }
+                        }
+                        info!("[CACHE_SUMMARY] Cached {} dates out of {} fetched transactions", total_cached, response.data.len());
+                        drop(spill);
+                        Self::enforce_memory_budget(state).await;
+                        // Save to file after caching
+                        if let Err(e) = Self::save_cache_to_file(state).await {
+                            error!(error = ?e, "[SMART_FETCH] Failed to save cache to file");
+                        }
                     }
-                    return Ok(response);
+                    // Transaksi yang tanggalnya tidak bisa dipetakan ke key cache tetap dikembalikan
+                    uncached.extend(response.data.into_iter().filter(|t| {
+                        DateService::normalize_date_for_api(&t.tanggal_transaksi)
+                            .map(|normalized| !missing_dates.contains(&normalized))
+                            .unwrap_or(true)
+                    }));
                 }
                 Err(e) => {
-                    // If unauthorized, don't retry - return immediately
                     if matches!(e, DebugAppError::Unauthorized(_)) {
-                        error!("[RETRY] Unauthorized error for {} - stopping retries", payload.from);
+                        state.set_unauthorized(state.config().account_for(store), true).await;
                         return Err(e);
                     }
-                    
-                    last_error = Some(e);
-                    if attempt < max_retries {
-                        let delay = Duration::from_millis(1000 * attempt as u64);
-                        warn!("[RETRY] Attempt {} failed for {}, retrying in {:?}", attempt, payload.from, delay);
-                        sleep(delay).await;
-                    }
+                    error!(from = %from, to = %to, error = ?e, "[SMART_FETCH] Failed to fetch range");
                 }
             }
         }
-        
-        Err(last_error.unwrap())
+
+        // Tanggal yang datanya dikirim lewat `uncached` tidak dibaca lagi dari cache (spill-nya tidak terbaca)
+        dates.retain(|date| !not_cached.contains(date));
+        state.set_unauthorized(state.config().account_for(store), false).await;
+        Ok(SmartFill {
+            store: store.to_string(),
+            dates,
+            message: format!("Fetched {} missing ranges", ranges_count),
+            uncached,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::transaksi;
+    use crate::models::ChangeKind;
+    use std::path::PathBuf;
+
+    fn temp_state(name: &str) -> (AppState, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("cache_load_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = Config::default();
+        config.upstream.store_id = "264".to_string();
+        config.cache.file_path = dir.join("cache_backup.json").to_string_lossy().to_string();
+        config.cache.spill_dir = dir.join("spill").to_string_lossy().to_string();
+        (AppState::new(config), dir)
+    }
+
+    fn entries(dir: &PathBuf) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn read_only_load_does_not_rewrite_a_migrated_file() {
+        let (state, dir) = temp_state("migrated");
+        let transaksi = transaksi("01/10/2025", "INV-1", 12500);
+        // Format v1: map tanggal langsung tanpa envelope
+        let v1 = serde_json::json!({ "01/10/2025": [transaksi] }).to_string();
+        let path = dir.join("cache_backup.json");
+        std::fs::write(&path, &v1).unwrap();
+
+        assert_eq!(CacheService::load_store_read_only(&state, "264").await.unwrap(), 1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), v1);
+        assert_eq!(entries(&dir), vec!["cache_backup.json"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn read_only_load_fails_without_moving_an_unreadable_file() {
+        let (state, dir) = temp_state("unreadable");
+        std::fs::write(dir.join("cache_backup.json"), "{ not json").unwrap();
+
+        assert!(CacheService::load_store_read_only(&state, "264").await.is_err());
+        assert_eq!(entries(&dir), vec!["cache_backup.json"]);
+
+        // Load server (tanpa CACHE_STRICT_LOAD) memindah file ke samping
+        assert!(state.load_cache_from_file().await.is_ok());
+        let after = entries(&dir);
+        assert!(after.iter().all(|name| name != "cache_backup.json"), "{:?}", after);
+        assert!(after.iter().any(|name| name.starts_with("cache_backup.json.unreadable-")), "{:?}", after);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn ensure_hot_reloads_a_spilled_date_and_remove_date_deletes_its_file() {
+        let (state, dir) = temp_state("spill_guard");
+        let config = state.config();
+        let transaksi = transaksi("01/10/2025", "INV-1", 12500);
+        let (daily, _) = DailyTransactions::from_vec(vec![transaksi]);
+        CacheSpillService::write(&CacheService::spill_dir(&config, "264"), "264", "01/10/2025", &daily).await.unwrap();
+        state.cache.write().await.insert_cold("264", "01/10/2025".to_string(), 1);
+
+        let spill = state.spill_lock.lock().await;
+        CacheService::ensure_hot(&state, &config, "264", "01/10/2025", &spill).await.unwrap();
+        assert_eq!(state.cache.read().await.peek("264", "01/10/2025").map(|daily| daily.len()), Some(1));
+
+        assert!(CacheService::remove_date(&state, &config, "264", "01/10/2025", &spill).await);
+        assert!(!state.cache.read().await.contains("264", "01/10/2025"));
+        assert!(entries(&dir.join("spill")).is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn replace_range_drops_voided_rows_and_logs_the_void_once() {
+        let (state, dir) = temp_state("replace_range");
+        let (kept, empty) = ("01/10/2025", "02/10/2025");
+        let fresh = vec![transaksi(kept, "INV-1", 100), transaksi(kept, "INV-2", 200)];
+        let mut old = fresh.clone();
+        old.push(transaksi(kept, "INV-3", 300));
+        {
+            let mut cache = state.cache.write().await;
+            cache.insert("264", kept.to_string(), DailyTransactions::from_vec(old).0);
+            cache.insert("264", empty.to_string(), DailyTransactions::from_vec(vec![transaksi(empty, "INV-9", 900)]).0);
+        }
+
+        let dates = vec![kept.to_string(), empty.to_string()];
+        let first = CacheService::replace_range(&state, "264", dates.clone(), &fresh).await;
+        let second = CacheService::replace_range(&state, "264", dates, &fresh).await;
+
+        let removed: Vec<&str> = first.changes.iter().chain(&second.changes)
+            .filter(|change| matches!(change.kind, ChangeKind::Removed))
+            .map(|change| change.no_nota.as_str())
+            .collect();
+        assert_eq!(removed, vec!["INV-3"]);
+        assert!(second.changes.is_empty());
+        assert_eq!((second.replaced, second.empty_dates), (1, 1));
+
+        let cache = state.cache.read().await;
+        assert_eq!(Transaksi::total_amount(cache.peek("264", kept).unwrap()), Transaksi::total_amount(&fresh));
+        // Balasan kosong tidak mengosongkan cache tanggal itu
+        assert_eq!(cache.transaction_count("264", empty), Some(1));
+        let _ = std::fs::remove_dir_all(&dir);
     }
-}
\ No newline at end of file
+}
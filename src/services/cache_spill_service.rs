@@ -0,0 +1,78 @@
+use crate::errors::DebugAppError;
+use crate::models::{DailyTransactions, Transaksi};
+use crate::services::{CacheFileService, DateService};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+use tokio::fs;
+
+/// Isi satu file spill
+pub struct SpilledDate {
+    pub transactions: Vec<Transaksi>,
+    /// Waktu file ditulis, untuk memilih data terbaru kalau tanggal juga ada di file backup
+    pub created_at: DateTime<Utc>,
+}
+
+/// File per tanggal untuk tanggal yang dikeluarkan dari memory (`CACHE_SPILL_DIR`).
+/// Formatnya sama dengan file backup (envelope `CacheFile`) berisi satu tanggal, jadi ikut
+/// migrasi schema dan cek store yang sama.
+pub struct CacheSpillService;
+
+impl CacheSpillService {
+    /// `<spill_dir>/2025-10-01.json` untuk key `01/10/2025`
+    pub fn path(dir: &str, date: &str) -> Result<String, DebugAppError> {
+        let parsed = DateService::parse_date(date)
+            .map_err(|_| DebugAppError::DateParse(format!("Invalid cache date key '{}'", date)))?;
+        Ok(format!("{}/{}.json", dir.trim_end_matches('/'), parsed.format("%Y-%m-%d")))
+    }
+
+    pub async fn write(dir: &str, store_id: &str, date: &str, daily: &DailyTransactions) -> Result<(), DebugAppError> {
+        fs::create_dir_all(dir).await
+            .map_err(|e| DebugAppError::FileWrite(format!("Failed to create spill dir {}: {}", dir, e)))?;
+        let dates = HashMap::from([(date, daily)]);
+        CacheFileService::write(&Self::path(dir, date)?, store_id, &dates).await
+    }
+
+    /// `Ok(None)` kalau file tidak ada
+    pub async fn read(dir: &str, store_id: &str, date: &str) -> Result<Option<SpilledDate>, DebugAppError> {
+        let path = Self::path(dir, date)?;
+        let Some(loaded) = CacheFileService::read(&path, store_id).await? else {
+            return Ok(None);
+        };
+        let created_at = loaded.file.created_at;
+        let mut dates = loaded.file.dates;
+        let transactions = dates.remove(date).filter(|_| dates.is_empty()).ok_or_else(|| {
+            DebugAppError::Serialization(format!("Spill file {} does not hold exactly the date {}", path, date))
+        })?;
+        Ok(Some(SpilledDate { transactions, created_at }))
+    }
+
+    /// Hapus file spill; file yang tidak ada bukan error
+    pub async fn remove(dir: &str, date: &str) -> Result<(), DebugAppError> {
+        let path = Self::path(dir, date)?;
+        match fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(DebugAppError::FileWrite(format!("Failed to remove {}: {}", path, e))),
+        }
+    }
+
+    /// Key tanggal (DD/MM/YYYY) dari nama file `*.json` di folder spill; folder belum ada = kosong
+    pub async fn list(dir: &str) -> Result<Vec<String>, DebugAppError> {
+        let mut entries = match fs::read_dir(dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(DebugAppError::FileWrite(format!("Failed to read spill dir {}: {}", dir, e))),
+        };
+        let mut dates = Vec::new();
+        while let Some(entry) = entries.next_entry().await
+            .map_err(|e| DebugAppError::FileWrite(format!("Failed to read spill dir {}: {}", dir, e)))?
+        {
+            let name = entry.file_name().to_string_lossy().to_string();
+            // File lain (`.tmp`, `.unreadable-*`, `.bak`) bukan spill aktif
+            if let Some(date) = name.strip_suffix(".json").and_then(|stem| NaiveDate::parse_from_str(stem, "%Y-%m-%d").ok()) {
+                dates.push(date.format("%d/%m/%Y").to_string());
+            }
+        }
+        Ok(dates)
+    }
+}
@@ -0,0 +1,105 @@
+use crate::config::Config;
+use crate::state::AppState;
+use std::env;
+use std::time::SystemTime;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::time::{interval, Duration, MissedTickBehavior};
+use tracing::{error, info, warn};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// Handle untuk mengganti filter log saat runtime
+pub type LogLevelHandle = reload::Handle<EnvFilter, Registry>;
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+pub struct ConfigService;
+
+impl ConfigService {
+    /// Reload config saat SIGHUP atau saat mtime file config berubah (dicek tiap 5 detik)
+    pub fn spawn_watcher(state: AppState, log_handle: LogLevelHandle) {
+        tokio::spawn(async move {
+            let mut hangup = match signal(SignalKind::hangup()) {
+                Ok(hangup) => hangup,
+                Err(e) => {
+                    error!("[CONFIG] Cannot listen for SIGHUP, hot-reload disabled: {}", e);
+                    return;
+                }
+            };
+            let mut ticker = interval(WATCH_INTERVAL);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            let mut last_modified = Self::config_file_modified().await;
+
+            loop {
+                tokio::select! {
+                    _ = hangup.recv() => {
+                        info!("[CONFIG] SIGHUP received, reloading configuration");
+                    }
+                    _ = ticker.tick() => {
+                        let modified = Self::config_file_modified().await;
+                        if modified == last_modified {
+                            continue;
+                        }
+                        last_modified = modified;
+                        info!("[CONFIG] Config file changed, reloading configuration");
+                    }
+                }
+                Self::reload(&state, &log_handle).await;
+            }
+        });
+    }
+
+    /// Load ulang dan pasang config baru secara atomik. Kalau validasi gagal, config lama tetap dipakai.
+    pub async fn reload(state: &AppState, log_handle: &LogLevelHandle) {
+        let current = state.config();
+        let reload = match Config::reload(&current) {
+            Ok(reload) => reload,
+            Err(e) => {
+                for problem in e.0 {
+                    error!("[CONFIG] Reload rejected, keeping current config: {}", problem);
+                }
+                return;
+            }
+        };
+
+        for key in &reload.ignored {
+            warn!("[CONFIG] {} changed but only applies after a restart, keeping current value", key);
+        }
+        if reload.changed.is_empty() {
+            info!("[CONFIG] Reloaded, no changes");
+            return;
+        }
+        for line in &reload.changed {
+            info!("[CONFIG] {}", line);
+        }
+
+        let config = reload.config;
+        // RUST_LOG menang atas log_level dari config, sama seperti saat startup
+        if config.server.log_level != current.server.log_level && env::var("RUST_LOG").is_err() {
+            if let Err(e) = log_handle.reload(EnvFilter::new(&config.server.log_level)) {
+                warn!("[CONFIG] Failed to apply log level: {}", e);
+            }
+        }
+        // Cookie sesi server milik kredensial lama; login ulang dengan kredensial baru saat dibutuhkan
+        let credentials = |config: &Config, account: &str| {
+            config.upstream_for_account(account).map(|u| (u.username, u.password, u.cookie))
+        };
+        let changed_accounts: Vec<String> = config
+            .account_names()
+            .into_iter()
+            .filter(|account| credentials(&config, account) != credentials(&current, account))
+            .map(str::to_string)
+            .collect();
+
+        state.set_config(config);
+        for account in changed_accounts {
+            state.set_session_cookie(&account, None).await;
+            info!(account = %account, "[CONFIG] Upstream credentials changed, server session cleared");
+        }
+        info!("[CONFIG] Applied {} changes", reload.changed.len());
+    }
+
+    async fn config_file_modified() -> Option<SystemTime> {
+        let path = Config::config_file_path()?;
+        tokio::fs::metadata(path).await.ok()?.modified().ok()
+    }
+}
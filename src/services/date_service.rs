@@ -1,6 +1,4 @@
-#![allow(dead_code)]
-
-use chrono::NaiveDate;
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime};
 
 pub struct DateService;
 
@@ -26,6 +24,22 @@ impl DateService {
         NaiveDate::parse_from_str(date_str, "%d/%m/%Y")
     }
 
+    /// Parse timestamp transaksi (`waktu_transaksi`) dari beberapa format upstream
+    pub fn parse_datetime(datetime_str: &str) -> Option<NaiveDateTime> {
+        let formats = [
+            "%Y-%m-%d %H:%M:%S",
+            "%Y-%m-%d %H:%M",
+            "%d/%m/%Y %H:%M:%S",
+            "%d/%m/%Y %H:%M",
+            "%d-%m-%Y %H:%M:%S",
+            "%Y-%m-%dT%H:%M:%S",
+        ];
+
+        formats
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(datetime_str.trim(), format).ok())
+    }
+
     /// Convert any supported date format to DD/MM/YYYY for API (original format)
     pub fn normalize_date_for_api(date_str: &str) -> Result<String, chrono::ParseError> {
         let date = Self::parse_date(date_str)?;
@@ -49,8 +63,42 @@ impl DateService {
         Ok(dates)
     }
 
-    pub fn format_for_api(date_str: &str) -> Result<String, chrono::ParseError> {
-        let normalized = Self::normalize_date_for_api(date_str)?;
-        Ok(urlencoding::encode(&normalized).to_string())
+    /// Range diperlebar ke bulan penuh: tanggal 1 bulan `from` sampai akhir bulan `to` (DD/MM/YYYY)
+    pub fn whole_months(from: &str, to: &str) -> Result<(String, String), chrono::ParseError> {
+        let from = Self::parse_date(from)?;
+        let to = Self::parse_date(to)?;
+        let first = from.with_day(1).unwrap_or(from);
+        let last = to
+            .with_day(1)
+            .and_then(|d| d.checked_add_months(Months::new(1)))
+            .and_then(|d| d.pred_opt())
+            .unwrap_or(to);
+        Ok((first.format("%d/%m/%Y").to_string(), last.format("%d/%m/%Y").to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_months_widens_to_month_bounds() {
+        assert_eq!(
+            DateService::whole_months("15/10/2025", "20/10/2025").unwrap(),
+            ("01/10/2025".to_string(), "31/10/2025".to_string())
+        );
+        assert_eq!(
+            DateService::whole_months("2024-01-31", "2024-02-01").unwrap(),
+            ("01/01/2024".to_string(), "29/02/2024".to_string())
+        );
+        assert_eq!(
+            DateService::whole_months("03/12/2025", "03/12/2025").unwrap(),
+            ("01/12/2025".to_string(), "31/12/2025".to_string())
+        );
+    }
+
+    #[test]
+    fn whole_months_rejects_invalid_dates() {
+        assert!(DateService::whole_months("bukan tanggal", "20/10/2025").is_err());
     }
-}
\ No newline at end of file
+}
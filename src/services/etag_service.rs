@@ -0,0 +1,122 @@
+use crate::errors::DebugAppError;
+use axum::body::{to_bytes, Body};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+pub struct EtagService;
+
+impl EtagService {
+    /// ETag strong dari hash body yang dikirim. `Accept-Encoding` ikut di-hash karena compression
+    /// layer mengubah byte yang sampai ke client; ETag sama berarti response byte-identik.
+    pub fn for_body(headers: &HeaderMap, body: &[u8]) -> String {
+        let accept_encoding = headers
+            .get(header::ACCEPT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("");
+        let mut hasher = Sha256::new();
+        hasher.update(accept_encoding.as_bytes());
+        hasher.update(b"\n");
+        hasher.update(body);
+        format!("\"{:x}\"", hasher.finalize())
+    }
+
+    /// `If-None-Match` cocok? (weak comparison sesuai RFC 9110, `*` cocok dengan apa saja)
+    pub fn matches(headers: &HeaderMap, etag: &str) -> bool {
+        let Some(if_none_match) = headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) else {
+            return false;
+        };
+        let opaque = etag.trim_start_matches("W/");
+        if_none_match
+            .split(',')
+            .map(|candidate| candidate.trim())
+            .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == opaque)
+    }
+
+    /// Pasang ETag ke response sukses yang dibaca dari cache, atau `304` kalau `If-None-Match` cocok.
+    /// Berlaku juga untuk POST (`/data-cached`, `/export`): request itu hanya membaca cache, jadi
+    /// dashboard yang polling cukup dapat `304`. Jangan dipakai untuk response stream.
+    pub async fn respond(headers: &HeaderMap, response: Response) -> Response {
+        if !response.status().is_success() {
+            return response;
+        }
+        let (mut parts, body) = response.into_parts();
+        let bytes = match to_bytes(body, usize::MAX).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                error!(error = %e, "[ETAG] Failed to read response body");
+                return DebugAppError::Serialization(format!("Failed to read response body: {}", e)).into_response();
+            }
+        };
+        let etag = Self::for_body(headers, &bytes);
+        if Self::matches(headers, &etag) {
+            return Self::not_modified(&etag);
+        }
+        Self::set_headers(&mut parts.headers, &etag);
+        Response::from_parts(parts, Body::from(bytes))
+    }
+
+    pub fn not_modified(etag: &str) -> Response {
+        let mut response = StatusCode::NOT_MODIFIED.into_response();
+        Self::set_headers(response.headers_mut(), etag);
+        response
+    }
+
+    fn set_headers(headers: &mut HeaderMap, etag: &str) {
+        if let Ok(value) = HeaderValue::from_str(etag) {
+            headers.insert(header::ETAG, value);
+        }
+        headers.insert(header::VARY, HeaderValue::from_static("Accept, Accept-Encoding"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn if_none_match(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn etag_is_strong_and_depends_on_body_and_encoding() {
+        let etag = EtagService::for_body(&HeaderMap::new(), b"a");
+        assert!(etag.starts_with('"'));
+        assert_ne!(etag, EtagService::for_body(&HeaderMap::new(), b"b"));
+        let mut gzip = HeaderMap::new();
+        gzip.insert(header::ACCEPT_ENCODING, HeaderValue::from_static("gzip"));
+        assert_ne!(etag, EtagService::for_body(&gzip, b"a"));
+    }
+
+    #[test]
+    fn weak_comparison_ignores_prefix_and_accepts_lists() {
+        let etag = EtagService::for_body(&HeaderMap::new(), b"a");
+        assert!(EtagService::matches(&if_none_match(&etag), &etag));
+        assert!(EtagService::matches(&if_none_match(&format!("W/{}", etag)), &etag));
+        assert!(EtagService::matches(&if_none_match(&format!("\"other\", {}", etag)), &etag));
+        assert!(EtagService::matches(&if_none_match("*"), &etag));
+        assert!(!EtagService::matches(&if_none_match("\"other\""), &etag));
+        assert!(!EtagService::matches(&HeaderMap::new(), &etag));
+    }
+
+    #[tokio::test]
+    async fn respond_answers_not_modified_only_for_a_matching_etag() {
+        let response = EtagService::respond(&HeaderMap::new(), "body".into_response()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+        assert_eq!(response.headers()[header::VARY], "Accept, Accept-Encoding");
+        assert_eq!(to_bytes(response.into_body(), usize::MAX).await.unwrap(), "body");
+
+        let cached = EtagService::respond(&if_none_match(&etag), "body".into_response()).await;
+        assert_eq!(cached.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(cached.headers()[header::ETAG], etag.as_str());
+        let changed = EtagService::respond(&if_none_match(&etag), "changed".into_response()).await;
+        assert_eq!(changed.status(), StatusCode::OK);
+
+        let failed = EtagService::respond(&if_none_match("*"), StatusCode::NOT_FOUND.into_response()).await;
+        assert_eq!(failed.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,444 @@
+use crate::config::ExportConfig;
+use crate::errors::DebugAppError;
+use crate::models::{CsvColumn, CsvOptions, DailySummary, ExportFormat, ExportQuery, Money, PartitionFile, Payload, Transaksi};
+use crate::services::DateService;
+use arrow_array::{ArrayRef, Date32Array, Decimal128Array, RecordBatch, StringArray, TimestampMillisecondArray};
+use arrow_schema::{ArrowError, DataType, Field, Schema, TimeUnit};
+use axum::body::Body;
+use axum::http::{header, HeaderMap};
+use chrono::format::StrftimeItems;
+use chrono::NaiveDate;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use rust_decimal::prelude::ToPrimitive;
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+use futures_util::stream;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Jumlah baris per chunk body CSV
+const CSV_CHUNK_ROWS: usize = 1000;
+
+pub const XLSX_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+pub struct ExportService;
+
+impl ExportService {
+    /// `?format=` menang; kalau tidak ada, lihat header `Accept`
+    pub fn requested_format(headers: &HeaderMap, query: &ExportQuery) -> ExportFormat {
+        if let Some(format) = query.format {
+            return format;
+        }
+
+        let accept = headers
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("");
+        if accept.contains("text/csv") {
+            ExportFormat::Csv
+        } else if accept.contains(XLSX_CONTENT_TYPE) {
+            ExportFormat::Xlsx
+        } else {
+            ExportFormat::Json
+        }
+    }
+
+    /// Opsi CSV dari query, default dari config (`CSV_COLUMNS`, `CSV_DELIMITER`, `CSV_DATE_FORMAT`)
+    pub fn csv_options(query: &ExportQuery, defaults: &ExportConfig) -> Result<CsvOptions, DebugAppError> {
+        let columns_raw = query.columns.clone()
+            .or_else(|| defaults.csv_columns.clone());
+        let columns = match columns_raw {
+            Some(raw) => raw
+                .split(',')
+                .filter(|name| !name.trim().is_empty())
+                .map(|name| CsvColumn::parse(name)
+                    .ok_or_else(|| DebugAppError::InvalidInput(format!("Unknown CSV column: {}", name))))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => CsvColumn::DEFAULT_ORDER.to_vec(),
+        };
+        if columns.is_empty() {
+            return Err(DebugAppError::InvalidInput("At least one CSV column is required".to_string()));
+        }
+
+        let delimiter_raw = query.delimiter.clone()
+            .or_else(|| defaults.csv_delimiter.clone())
+            .unwrap_or_else(|| ",".to_string());
+        let delimiter = CsvOptions::parse_delimiter(&delimiter_raw)
+            .ok_or_else(|| DebugAppError::InvalidInput(format!("Invalid CSV delimiter: {}", delimiter_raw)))?;
+
+        let date_format = query.date_format.clone()
+            .or_else(|| defaults.csv_date_format.clone())
+            .unwrap_or_else(|| "%d/%m/%Y".to_string());
+        // `%H`/`%z` lolos parser tapi gagal saat dipakai ke `NaiveDate`; `to_string()` akan panic
+        let sample = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap_or_default();
+        let mut formatted = String::new();
+        if write!(formatted, "{}", sample.format_with_items(StrftimeItems::new(&date_format))).is_err() {
+            return Err(DebugAppError::InvalidInput(format!("Invalid date format: {}", date_format)));
+        }
+
+        let decimal_separator = match query.decimal_separator.as_deref() {
+            None | Some(".") => '.',
+            Some(",") => ',',
+            Some(other) => return Err(DebugAppError::InvalidInput(format!("Invalid decimal separator: {}", other))),
+        };
+
+        Ok(CsvOptions {
+            columns,
+            delimiter,
+            date_format,
+            decimal_separator,
+        })
+    }
+
+    /// CSV sebagai stream per `CSV_CHUNK_ROWS` baris. Baris sudah di memory (dedup dan ETag butuh
+    /// seluruh range), jadi yang tidak perlu dibuat utuh adalah hasil CSV-nya.
+    pub fn csv_body(transactions: Vec<Transaksi>, options: CsvOptions) -> Body {
+        let (transactions, options) = (Arc::new(transactions), Arc::new(options));
+        // Chunk pertama selalu dikirim (header), termasuk kalau tidak ada baris
+        let chunks = stream::unfold(Some(0usize), move |start| {
+            let (transactions, options) = (transactions.clone(), options.clone());
+            async move {
+                let start = start?;
+                let end = (start + CSV_CHUNK_ROWS).min(transactions.len());
+                let chunk = Self::csv_chunk(&transactions[start..end], &options, start == 0)
+                    .map_err(|e| std::io::Error::other(format!("{:?}", e)));
+                Some((chunk, (end < transactions.len()).then_some(end)))
+            }
+        });
+        Body::from_stream(chunks)
+    }
+
+    fn csv_chunk(transactions: &[Transaksi], options: &CsvOptions, header: bool) -> Result<Vec<u8>, DebugAppError> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(options.delimiter)
+            .has_headers(false)
+            .from_writer(Vec::new());
+
+        if header {
+            writer.write_record(options.columns.iter().map(|c| c.header()))
+                .map_err(|e| DebugAppError::Serialization(e.to_string()))?;
+        }
+
+        for transaksi in transactions {
+            let row: Vec<String> = options.columns
+                .iter()
+                .map(|column| Self::csv_field(transaksi, *column, options))
+                .collect();
+            writer.write_record(&row)
+                .map_err(|e| DebugAppError::Serialization(e.to_string()))?;
+        }
+
+        writer.into_inner()
+            .map_err(|e| DebugAppError::Serialization(e.to_string()))
+    }
+
+    fn csv_field(transaksi: &Transaksi, column: CsvColumn, options: &CsvOptions) -> String {
+        match column {
+            CsvColumn::TanggalTransaksi => DateService::parse_date(&transaksi.tanggal_transaksi)
+                .map(|d| d.format(&options.date_format).to_string())
+                .unwrap_or_else(|_| transaksi.tanggal_transaksi.clone()),
+            CsvColumn::WaktuTransaksi => transaksi.waktu_transaksi.clone(),
+            CsvColumn::NoNota => transaksi.no_nota.clone(),
+            CsvColumn::Keterangan => transaksi.keterangan.clone(),
+            CsvColumn::TotalTagihan => {
+                let amount = transaksi.total_tagihan.amount.to_string();
+                if options.decimal_separator == ',' {
+                    amount.replace('.', ",")
+                } else {
+                    amount
+                }
+            }
+            CsvColumn::Currency => transaksi.total_tagihan.currency.code().to_string(),
+            CsvColumn::ParseError => transaksi.parse_error.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Total per tanggal transaksi, urut dari tanggal paling awal
+    pub fn daily_summary(transactions: &[Transaksi]) -> Vec<DailySummary> {
+        let mut per_day: BTreeMap<Option<chrono::NaiveDate>, (usize, Money)> = BTreeMap::new();
+        for transaksi in transactions {
+            let date = DateService::parse_date(&transaksi.tanggal_transaksi).ok();
+            let entry = per_day.entry(date).or_insert((0, Money::zero()));
+            entry.0 += 1;
+            entry.1 = entry.1 + transaksi.total_tagihan;
+        }
+
+        per_day
+            .into_iter()
+            .map(|(date, (total_transaksi, total_tagihan))| DailySummary {
+                date,
+                total_transaksi,
+                total_tagihan,
+            })
+            .collect()
+    }
+
+    /// Workbook dengan sheet transaksi (tanggal & nominal bertipe) dan sheet ringkasan harian
+    pub fn to_xlsx(transactions: &[Transaksi]) -> Result<Vec<u8>, DebugAppError> {
+        Self::build_xlsx(transactions)
+            .map_err(|e| DebugAppError::Serialization(format!("XLSX error: {}", e)))
+    }
+
+    fn build_xlsx(transactions: &[Transaksi]) -> Result<Vec<u8>, XlsxError> {
+        let header_format = Format::new().set_bold();
+        let date_format = Format::new().set_num_format("dd/mm/yyyy");
+        let datetime_format = Format::new().set_num_format("dd/mm/yyyy hh:mm:ss");
+        let amount_format = Format::new().set_num_format("#,##0.00");
+        let total_format = Format::new().set_bold().set_num_format("#,##0.00");
+
+        let mut sheet = Worksheet::new();
+        sheet.set_name("Transaksi")?;
+        let headers = ["tanggal_transaksi", "waktu_transaksi", "no_nota", "keterangan", "total_tagihan", "currency"];
+        for (col, title) in headers.iter().enumerate() {
+            sheet.write_string_with_format(0, col as u16, *title, &header_format)?;
+        }
+
+        for (i, transaksi) in transactions.iter().enumerate() {
+            let row = (i + 1) as u32;
+            match DateService::parse_date(&transaksi.tanggal_transaksi) {
+                Ok(date) => sheet.write_datetime_with_format(row, 0, date, &date_format)?,
+                Err(_) => sheet.write_string(row, 0, &transaksi.tanggal_transaksi)?,
+            };
+            match DateService::parse_datetime(&transaksi.waktu_transaksi) {
+                Some(datetime) => sheet.write_datetime_with_format(row, 1, datetime, &datetime_format)?,
+                None => sheet.write_string(row, 1, &transaksi.waktu_transaksi)?,
+            };
+            sheet.write_string(row, 2, &transaksi.no_nota)?;
+            sheet.write_string(row, 3, &transaksi.keterangan)?;
+            sheet.write_number_with_format(row, 4, Self::amount_as_f64(&transaksi.total_tagihan), &amount_format)?;
+            sheet.write_string(row, 5, transaksi.total_tagihan.currency.code())?;
+        }
+        sheet.set_freeze_panes(1, 0)?;
+        sheet.autofit();
+
+        let summary = Self::daily_summary(transactions);
+        let mut summary_sheet = Worksheet::new();
+        summary_sheet.set_name("Ringkasan Harian")?;
+        for (col, title) in ["tanggal", "jumlah_transaksi", "total_tagihan"].iter().enumerate() {
+            summary_sheet.write_string_with_format(0, col as u16, *title, &header_format)?;
+        }
+        for (i, day) in summary.iter().enumerate() {
+            let row = (i + 1) as u32;
+            match day.date {
+                Some(date) => summary_sheet.write_datetime_with_format(row, 0, date, &date_format)?,
+                None => summary_sheet.write_string(row, 0, "(tanggal tidak valid)")?,
+            };
+            summary_sheet.write_number(row, 1, day.total_transaksi as f64)?;
+            summary_sheet.write_number_with_format(row, 2, Self::amount_as_f64(&day.total_tagihan), &amount_format)?;
+        }
+
+        // Grand total dihitung exact di Rust (Decimal), bukan formula Excel
+        let total_row = (summary.len() + 1) as u32;
+        summary_sheet.write_string_with_format(total_row, 0, "TOTAL", &header_format)?;
+        summary_sheet.write_number_with_format(total_row, 1, transactions.len() as f64, &header_format)?;
+        summary_sheet.write_number_with_format(
+            total_row,
+            2,
+            Self::amount_as_f64(&Transaksi::total_amount(transactions)),
+            &total_format,
+        )?;
+        summary_sheet.set_freeze_panes(1, 0)?;
+        summary_sheet.autofit();
+
+        let mut workbook = Workbook::new();
+        workbook.push_worksheet(sheet);
+        workbook.push_worksheet(summary_sheet);
+        workbook.save_to_buffer()
+    }
+
+    fn amount_as_f64(money: &Money) -> f64 {
+        money.amount.to_f64().unwrap_or(0.0)
+    }
+
+    /// Schema Parquet yang stabil untuk `Transaksi` (jangan ubah urutan/tipe tanpa versi baru)
+    pub fn parquet_schema() -> Arc<Schema> {
+        Arc::new(Schema::new(vec![
+            Field::new("tanggal_transaksi", DataType::Date32, true),
+            Field::new("waktu_transaksi", DataType::Timestamp(TimeUnit::Millisecond, None), true),
+            Field::new("no_nota", DataType::Utf8, false),
+            Field::new("keterangan", DataType::Utf8, false),
+            Field::new("total_tagihan", DataType::Decimal128(18, 2), false),
+            Field::new("currency", DataType::Utf8, false),
+            Field::new("parse_error", DataType::Utf8, true),
+        ]))
+    }
+
+    fn record_batch(transactions: &[Transaksi]) -> Result<RecordBatch, ArrowError> {
+        let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+
+        let tanggal: Date32Array = transactions
+            .iter()
+            .map(|t| DateService::parse_date(&t.tanggal_transaksi).ok().map(|d| (d - epoch).num_days() as i32))
+            .collect();
+        let waktu: TimestampMillisecondArray = transactions
+            .iter()
+            .map(|t| DateService::parse_datetime(&t.waktu_transaksi).map(|dt| dt.and_utc().timestamp_millis()))
+            .collect();
+        let no_nota = StringArray::from_iter_values(transactions.iter().map(|t| t.no_nota.as_str()));
+        let keterangan = StringArray::from_iter_values(transactions.iter().map(|t| t.keterangan.as_str()));
+        let total_tagihan = transactions
+            .iter()
+            .map(|t| {
+                let mut amount = t.total_tagihan.amount.round_dp(2);
+                amount.rescale(2);
+                Some(amount.mantissa())
+            })
+            .collect::<Decimal128Array>()
+            .with_precision_and_scale(18, 2)?;
+        let currency = StringArray::from_iter_values(transactions.iter().map(|t| t.total_tagihan.currency.code()));
+        let parse_error: StringArray = transactions.iter().map(|t| t.parse_error.as_deref()).collect();
+
+        RecordBatch::try_new(
+            Self::parquet_schema(),
+            vec![
+                Arc::new(tanggal) as ArrayRef,
+                Arc::new(waktu),
+                Arc::new(no_nota),
+                Arc::new(keterangan),
+                Arc::new(total_tagihan),
+                Arc::new(currency),
+                Arc::new(parse_error),
+            ],
+        )
+    }
+
+    fn write_parquet<W: Write + Send>(writer: W, transactions: &[Transaksi]) -> Result<(), DebugAppError> {
+        let to_error = |e: &dyn std::fmt::Display| DebugAppError::Serialization(format!("Parquet error: {}", e));
+
+        let batch = Self::record_batch(transactions).map_err(|e| to_error(&e))?;
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let mut arrow_writer = ArrowWriter::try_new(writer, Self::parquet_schema(), Some(props))
+            .map_err(|e| to_error(&e))?;
+        arrow_writer.write(&batch).map_err(|e| to_error(&e))?;
+        arrow_writer.close().map_err(|e| to_error(&e))?;
+        Ok(())
+    }
+
+    pub fn to_parquet(transactions: &[Transaksi]) -> Result<Vec<u8>, DebugAppError> {
+        let mut buffer = Vec::new();
+        Self::write_parquet(&mut buffer, transactions)?;
+        Ok(buffer)
+    }
+
+    /// Tulis Parquet ke `dir/month=YYYY-MM/transaksi.parquet` (hive partitioning, file per bulan di-overwrite).
+    /// `transactions` harus berisi bulan penuh untuk tiap bulan yang disentuh. Ditulis ke `.tmp` lalu
+    /// di-rename, jadi partisi lama tetap utuh kalau penulisan gagal. Blocking I/O, panggil lewat `spawn_blocking`.
+    pub fn write_parquet_partitions(dir: &Path, transactions: &[Transaksi]) -> Result<Vec<PartitionFile>, DebugAppError> {
+        let mut per_month: BTreeMap<String, Vec<Transaksi>> = BTreeMap::new();
+        for transaksi in transactions {
+            let month = DateService::parse_date(&transaksi.tanggal_transaksi)
+                .map(|d| d.format("%Y-%m").to_string())
+                .unwrap_or_else(|_| "unknown".to_string());
+            per_month.entry(month).or_default().push(transaksi.clone());
+        }
+
+        let mut written = Vec::new();
+        for (month, mut rows) in per_month {
+            rows.sort_by(|a, b| {
+                let key = |t: &Transaksi| (DateService::parse_date(&t.tanggal_transaksi).ok(), t.waktu_transaksi.clone());
+                key(a).cmp(&key(b))
+            });
+
+            let partition_dir = dir.join(format!("month={}", month));
+            std::fs::create_dir_all(&partition_dir)
+                .map_err(|e| DebugAppError::FileWrite(e.to_string()))?;
+            let path = partition_dir.join("transaksi.parquet");
+            let tmp_path = partition_dir.join("transaksi.parquet.tmp");
+            let file = std::fs::File::create(&tmp_path)
+                .map_err(|e| DebugAppError::FileWrite(e.to_string()))?;
+            if let Err(e) = Self::write_parquet(file, &rows) {
+                let _ = std::fs::remove_file(&tmp_path);
+                return Err(e);
+            }
+            std::fs::rename(&tmp_path, &path)
+                .map_err(|e| DebugAppError::FileWrite(format!("Failed to replace {}: {}", path.display(), e)))?;
+
+            written.push(PartitionFile {
+                month,
+                path: path.display().to_string(),
+                rows: rows.len(),
+            });
+        }
+
+        Ok(written)
+    }
+
+    pub fn filename(payload: &Payload, extension: &str) -> String {
+        let label = |date: &str| DateService::parse_date(date)
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|_| "unknown".to_string());
+        format!("transaksi_{}_{}.{}", label(&payload.from), label(&payload.to), extension)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::transaksi;
+
+    fn csv_query(date_format: &str) -> ExportQuery {
+        ExportQuery { date_format: Some(date_format.to_string()), ..Default::default() }
+    }
+
+    #[test]
+    fn csv_date_format_rejects_time_and_zone_specifiers() {
+        let defaults = ExportConfig::default();
+        for format in ["%H:%M", "%z", "%Y-%m-%d %S", "%Q"] {
+            assert!(
+                matches!(ExportService::csv_options(&csv_query(format), &defaults), Err(DebugAppError::InvalidInput(_))),
+                "{} should be rejected",
+                format
+            );
+        }
+        let options = ExportService::csv_options(&csv_query("%Y%m%d"), &defaults).unwrap();
+        assert_eq!(options.date_format, "%Y%m%d");
+    }
+
+    #[tokio::test]
+    async fn csv_body_streams_header_once_across_chunks() {
+        let options = ExportService::csv_options(&ExportQuery::default(), &ExportConfig::default()).unwrap();
+        let rows: Vec<Transaksi> = (0..CSV_CHUNK_ROWS + 5).map(|i| transaksi("2025-10-01", &format!("N{}", i), 12500)).collect();
+
+        let body = ExportService::csv_body(rows, options.clone());
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        let text = String::from_utf8(bytes.to_vec()).unwrap();
+        let header = String::from_utf8(ExportService::csv_chunk(&[], &options, true).unwrap()).unwrap();
+
+        assert_eq!(text.lines().count(), CSV_CHUNK_ROWS + 6);
+        assert_eq!(text.matches(header.trim_end()).count(), 1);
+        assert!(text.contains("01/10/2025"));
+
+        let empty = ExportService::csv_body(Vec::new(), options);
+        let bytes = axum::body::to_bytes(empty, usize::MAX).await.unwrap();
+        assert_eq!(bytes, header.as_bytes());
+    }
+
+    #[test]
+    fn parquet_partitions_are_replaced_without_leftover_tmp() {
+        let dir = std::env::temp_dir().join(format!("parquet-partitions-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let rows = vec![
+            transaksi("2025-10-01", "A1", 12500),
+            transaksi("2025-10-31", "A2", 12500),
+            transaksi("2025-11-01", "B1", 12500),
+        ];
+        let files = ExportService::write_parquet_partitions(&dir, &rows).unwrap();
+        assert_eq!(files.iter().map(|f| (f.month.as_str(), f.rows)).collect::<Vec<_>>(), vec![("2025-10", 2), ("2025-11", 1)]);
+
+        let files = ExportService::write_parquet_partitions(&dir, &rows[..1]).unwrap();
+        assert_eq!(files.len(), 1);
+        let october = dir.join("month=2025-10");
+        assert!(october.join("transaksi.parquet").exists());
+        assert!(!october.join("transaksi.parquet.tmp").exists());
+        assert!(dir.join("month=2025-11/transaksi.parquet").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,258 @@
+use crate::errors::DebugAppError;
+use crate::models::{Money, PageInfo, SortField, SortOrder, Transaksi, TransactionFilter};
+use crate::services::DateService;
+use chrono::NaiveTime;
+use std::cmp::Ordering;
+
+pub struct FilterService;
+
+pub struct FilteredPage {
+    pub data: Vec<Transaksi>,
+    pub page: PageInfo,
+}
+
+impl FilterService {
+    /// Filter, sort lalu ambil satu halaman. Cursor adalah offset ke hasil yang sudah di-sort.
+    pub fn apply(data: Vec<Transaksi>, filter: &TransactionFilter) -> Result<FilteredPage, DebugAppError> {
+        let row_filter = RowFilter::new(filter)?;
+        let mut matching: Vec<Transaksi> = data
+            .into_iter()
+            .filter(|t| row_filter.matches(t))
+            .collect();
+
+        if let Some(field) = filter.sort {
+            let order = filter.order.unwrap_or_default();
+            matching.sort_by(|a, b| {
+                let ordering = Self::compare(a, b, field).then_with(|| a.dedup_key().cmp(&b.dedup_key()));
+                match order {
+                    SortOrder::Asc => ordering,
+                    SortOrder::Desc => ordering.reverse(),
+                }
+            });
+        }
+
+        let total_matching = matching.len();
+        let total_tagihan_matching = Transaksi::total_amount(&matching);
+
+        let offset = match filter.cursor.as_deref() {
+            Some(cursor) => cursor
+                .parse::<usize>()
+                .map_err(|_| DebugAppError::InvalidInput(format!("Invalid cursor: {}", cursor)))?,
+            None => 0,
+        };
+        let limit = filter.limit;
+        if limit == Some(0) {
+            return Err(DebugAppError::InvalidInput("limit must be greater than 0".to_string()));
+        }
+
+        let page_data: Vec<Transaksi> = matching
+            .into_iter()
+            .skip(offset)
+            .take(limit.unwrap_or(usize::MAX))
+            .collect();
+        let next_offset = offset + page_data.len();
+        let next_cursor = (limit.is_some() && next_offset < total_matching).then(|| next_offset.to_string());
+
+        Ok(FilteredPage {
+            data: page_data,
+            page: PageInfo {
+                total_matching,
+                total_tagihan_matching,
+                limit,
+                cursor: filter.cursor.clone(),
+                next_cursor,
+            },
+        })
+    }
+
+    /// Filter per baris saja (tanpa sort/paging) untuk response streaming.
+    /// Sort dan cursor butuh seluruh hasil di memory, jadi ditolak di mode ini.
+    pub fn row_filter(filter: &TransactionFilter) -> Result<RowFilter, DebugAppError> {
+        if filter.sort.is_some() || filter.order.is_some() || filter.limit.is_some() || filter.cursor.is_some() {
+            return Err(DebugAppError::InvalidInput(
+                "sort, order, limit and cursor are not supported for streaming responses".to_string(),
+            ));
+        }
+        RowFilter::new(filter)
+    }
+
+    fn compare(a: &Transaksi, b: &Transaksi, field: SortField) -> Ordering {
+        match field {
+            SortField::TanggalTransaksi => {
+                let date = |t: &Transaksi| DateService::parse_date(&t.tanggal_transaksi).ok();
+                date(a).cmp(&date(b)).then_with(|| Self::compare(a, b, SortField::WaktuTransaksi))
+            }
+            SortField::WaktuTransaksi => {
+                let datetime = |t: &Transaksi| DateService::parse_datetime(&t.waktu_transaksi);
+                datetime(a).cmp(&datetime(b)).then_with(|| a.waktu_transaksi.cmp(&b.waktu_transaksi))
+            }
+            SortField::TotalTagihan => a.total_tagihan.amount.cmp(&b.total_tagihan.amount),
+            SortField::NoNota => a.no_nota.cmp(&b.no_nota),
+            SortField::Keterangan => a.keterangan.to_lowercase().cmp(&b.keterangan.to_lowercase()),
+        }
+    }
+
+}
+
+/// Kriteria filter yang sudah di-parse, dipakai per transaksi
+pub struct RowFilter {
+    needle: Option<String>,
+    min_total: Option<Money>,
+    max_total: Option<Money>,
+    time_from: Option<NaiveTime>,
+    time_to: Option<NaiveTime>,
+}
+
+impl RowFilter {
+    fn new(filter: &TransactionFilter) -> Result<Self, DebugAppError> {
+        Ok(Self {
+            needle: filter.q.as_deref().map(|q| q.trim().to_lowercase()).filter(|q| !q.is_empty()),
+            min_total: Self::parse_amount("min_total", filter.min_total.as_deref())?,
+            max_total: Self::parse_amount("max_total", filter.max_total.as_deref())?,
+            time_from: Self::parse_time("time_from", filter.time_from.as_deref())?,
+            time_to: Self::parse_time("time_to", filter.time_to.as_deref())?,
+        })
+    }
+
+    pub fn matches(&self, t: &Transaksi) -> bool {
+        let text_match = match &self.needle {
+            Some(q) => t.keterangan.to_lowercase().contains(q) || t.no_nota.to_lowercase().contains(q),
+            None => true,
+        };
+        text_match
+            && self.min_total.is_none_or(|min| t.total_tagihan.amount >= min.amount)
+            && self.max_total.is_none_or(|max| t.total_tagihan.amount <= max.amount)
+            && Self::in_time_window(t, self.time_from, self.time_to)
+    }
+
+    /// Window jam (inklusif). Kalau `from > to` dianggap melewati tengah malam, mis. 22:00-02:00.
+    fn in_time_window(transaksi: &Transaksi, from: Option<NaiveTime>, to: Option<NaiveTime>) -> bool {
+        if from.is_none() && to.is_none() {
+            return true;
+        }
+        let Some(time) = DateService::parse_datetime(&transaksi.waktu_transaksi).map(|dt| dt.time()) else {
+            return false;
+        };
+
+        match (from, to) {
+            (Some(from), Some(to)) if from > to => time >= from || time <= to,
+            _ => from.is_none_or(|f| time >= f) && to.is_none_or(|t| time <= t),
+        }
+    }
+
+    fn parse_amount(label: &str, value: Option<&str>) -> Result<Option<Money>, DebugAppError> {
+        value
+            .map(|v| Money::parse_idr(v)
+                .map_err(|e| DebugAppError::InvalidInput(format!("Invalid {}: {}", label, e))))
+            .transpose()
+    }
+
+    fn parse_time(label: &str, value: Option<&str>) -> Result<Option<NaiveTime>, DebugAppError> {
+        value
+            .map(|v| NaiveTime::parse_from_str(v, "%H:%M:%S")
+                .or_else(|_| NaiveTime::parse_from_str(v, "%H:%M"))
+                .map_err(|_| DebugAppError::InvalidInput(format!("Invalid {}: {} (use HH:MM or HH:MM:SS)", label, v))))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::transaksi;
+    use rust_decimal::Decimal;
+
+
+    fn sample() -> Vec<Transaksi> {
+        vec![
+            transaksi("01/10/2025", "INV-4", 5000).at("23:30:00"),
+            transaksi("01/10/2025", "INV-1", 5000).at("08:00:00"),
+            transaksi("01/10/2025", "INV-3", 20000).at("12:15:00"),
+            transaksi("01/10/2025", "INV-2", 5000).at("01:45:00"),
+            transaksi("01/10/2025", "INV-5", 1000).at("09:00:00"),
+        ]
+    }
+
+    fn notas(page: &FilteredPage) -> Vec<&str> {
+        page.data.iter().map(|t| t.no_nota.as_str()).collect()
+    }
+
+    #[test]
+    fn cursor_pages_cover_every_row_once_in_sort_order() {
+        let mut filter = TransactionFilter {
+            sort: Some(SortField::TotalTagihan),
+            order: Some(SortOrder::Desc),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let mut seen = Vec::new();
+        loop {
+            let page = FilterService::apply(sample(), &filter).unwrap();
+            assert_eq!(page.page.total_matching, 5);
+            assert_eq!(page.page.cursor, filter.cursor);
+            seen.extend(notas(&page).into_iter().map(str::to_string));
+            match page.page.next_cursor {
+                Some(next) => filter.cursor = Some(next),
+                None => break,
+            }
+        }
+        // Nominal sama diurutkan stabil lewat dedup_key (dibalik ikut order desc)
+        assert_eq!(seen, vec!["INV-3", "INV-4", "INV-2", "INV-1", "INV-5"]);
+    }
+
+    #[test]
+    fn cursor_applies_after_filtering() {
+        let filter = TransactionFilter {
+            max_total: Some("5.000".to_string()),
+            sort: Some(SortField::WaktuTransaksi),
+            limit: Some(3),
+            cursor: Some("2".to_string()),
+            ..Default::default()
+        };
+        let page = FilterService::apply(sample(), &filter).unwrap();
+        assert_eq!(notas(&page), vec!["INV-5", "INV-4"]);
+        assert_eq!(page.page.total_matching, 4);
+        assert_eq!(page.page.total_tagihan_matching, Money::idr(Decimal::new(16000, 0)));
+        assert_eq!(page.page.next_cursor, None);
+
+        // Cursor lewat dari akhir: halaman kosong, bukan error
+        let past_end = TransactionFilter { cursor: Some("10".to_string()), ..filter };
+        let page = FilterService::apply(sample(), &past_end).unwrap();
+        assert!(page.data.is_empty());
+        assert_eq!(page.page.next_cursor, None);
+    }
+
+    #[test]
+    fn invalid_cursor_and_zero_limit_are_rejected() {
+        let bad_cursor = TransactionFilter { cursor: Some("abc".to_string()), ..Default::default() };
+        assert!(matches!(FilterService::apply(sample(), &bad_cursor), Err(DebugAppError::InvalidInput(_))));
+
+        let zero_limit = TransactionFilter { limit: Some(0), ..Default::default() };
+        assert!(matches!(FilterService::apply(sample(), &zero_limit), Err(DebugAppError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn streaming_rejects_sort_and_paging() {
+        let streaming = TransactionFilter { cursor: Some("2".to_string()), ..Default::default() };
+        assert!(FilterService::row_filter(&streaming).is_err());
+        let sorted = TransactionFilter { sort: Some(SortField::NoNota), ..Default::default() };
+        assert!(FilterService::row_filter(&sorted).is_err());
+
+        let row_filter = FilterService::row_filter(&TransactionFilter { q: Some("inv-3".to_string()), ..Default::default() }).unwrap();
+        let matching: Vec<_> = sample().into_iter().filter(|t| row_filter.matches(t)).collect();
+        assert_eq!(matching.len(), 1);
+    }
+
+    #[test]
+    fn time_window_can_cross_midnight() {
+        let filter = TransactionFilter {
+            time_from: Some("22:00".to_string()),
+            time_to: Some("02:00".to_string()),
+            sort: Some(SortField::NoNota),
+            ..Default::default()
+        };
+        let page = FilterService::apply(sample(), &filter).unwrap();
+        assert_eq!(notas(&page), vec!["INV-2", "INV-4"]);
+        assert_eq!(page.page.next_cursor, None);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::models::{CacheCheck, JobsCheck, PersistenceCheck, ReadinessChecks, ReadinessResponse, UpstreamCheck};
+use crate::state::{AppState, CacheLoadStatus};
+
+pub struct HealthService;
+
+impl HealthService {
+    /// Kumpulkan status readiness. Degraded kalau cache file gagal di-load, upstream menolak sesi,
+    /// atau persist cache terakhir gagal. Belum login (cookie belum ada) tidak dihitung degraded.
+    pub async fn readiness(state: &AppState) -> ReadinessResponse {
+        let health = state.health.read().await.clone();
+        let mut problems = Vec::new();
+
+        let (dates, transactions, memory_dates, memory_bytes) = {
+            let cache = state.cache.read().await;
+            (cache.len(), cache.transactions(), cache.hot_len(), cache.hot_bytes())
+        };
+        let (cache_status, cache_error) = match health.cache_load {
+            CacheLoadStatus::Pending => ("pending", None),
+            CacheLoadStatus::Loaded => ("loaded", None),
+            CacheLoadStatus::Failed(e) => {
+                problems.push(format!("cache file failed to load: {}", e));
+                ("failed", Some(e))
+            }
+        };
+
+        let mut rejected_accounts: Vec<String> = health.sessions_rejected.keys().cloned().collect();
+        rejected_accounts.sort();
+        for account in &rejected_accounts {
+            problems.push(format!("upstream rejected the session of account '{}'", account));
+        }
+        let session_accounts = state.session_accounts().await;
+
+        let running = state.running_job_count();
+
+        let (last_error_at, last_error) = health.persist_error.unzip();
+        if let Some(e) = &last_error {
+            problems.push(format!("last cache save failed: {}", e));
+        }
+
+        ReadinessResponse {
+            status: if problems.is_empty() { "ready" } else { "degraded" },
+            problems,
+            checks: ReadinessChecks {
+                cache: CacheCheck {
+                    status: cache_status,
+                    dates,
+                    transactions,
+                    memory_dates,
+                    memory_bytes,
+                    error: cache_error,
+                },
+                upstream: UpstreamCheck {
+                    last_success_at: health.last_upstream_success,
+                    authorized: rejected_accounts.is_empty(),
+                    session_rejected_at: health.sessions_rejected.values().max().copied(),
+                    rejected_accounts,
+                    server_session: !session_accounts.is_empty(),
+                    session_accounts,
+                },
+                jobs: JobsCheck { running },
+                persistence: PersistenceCheck {
+                    last_success_at: health.last_persist_success,
+                    last_error,
+                    last_error_at,
+                },
+            },
+        }
+    }
+}
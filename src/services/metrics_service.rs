@@ -0,0 +1,197 @@
+use crate::state::{AppState, JobStatus};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+/// Jenis kegagalan request upstream (label `kind` di `upstream_failures_total`)
+#[derive(Debug, Clone, Copy)]
+pub enum UpstreamFailure {
+    /// Diarahkan ke halaman login (cookie kadaluarsa)
+    Unauthorized,
+    /// Body bukan JSON (HTML error, maintenance page, ...)
+    NonJson,
+    Empty,
+    /// Body kelihatan JSON tapi gagal di-parse
+    InvalidJson,
+    /// Koneksi / timeout / gagal baca body
+    Request,
+}
+
+impl UpstreamFailure {
+    fn as_str(self) -> &'static str {
+        match self {
+            UpstreamFailure::Unauthorized => "unauthorized",
+            UpstreamFailure::NonJson => "non_json",
+            UpstreamFailure::Empty => "empty",
+            UpstreamFailure::InvalidJson => "invalid_json",
+            UpstreamFailure::Request => "request",
+        }
+    }
+}
+
+struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_duration: HistogramVec,
+    cache_lookups: IntCounterVec,
+    upstream_duration: HistogramVec,
+    upstream_failures: IntCounterVec,
+    upstream_pages: IntCounter,
+    jobs: IntGaugeVec,
+    cache_dates: IntGauge,
+    cache_transactions: IntGauge,
+    cache_memory_dates: IntGauge,
+    cache_memory_bytes: IntGauge,
+    cache_spill: IntCounterVec,
+    cache_persist_duration: HistogramVec,
+}
+
+// Satu registry per proses; TransactionService tidak memegang state jadi metrics dibuat global
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests by route, method and status"),
+            &["method", "route", "status"],
+        ).unwrap();
+        let http_duration = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "HTTP request latency by route and method"),
+            &["method", "route"],
+        ).unwrap();
+        let cache_lookups = IntCounterVec::new(
+            Opts::new("cache_lookups_total", "Per-date cache lookups, result is hit or miss"),
+            &["result"],
+        ).unwrap();
+        let upstream_duration = HistogramVec::new(
+            HistogramOpts::new("upstream_request_duration_seconds", "Upstream request latency by operation")
+                .buckets(vec![0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0]),
+            &["operation"],
+        ).unwrap();
+        let upstream_failures = IntCounterVec::new(
+            Opts::new("upstream_failures_total", "Failed upstream requests by failure kind"),
+            &["kind"],
+        ).unwrap();
+        let upstream_pages = IntCounter::new("upstream_pages_fetched_total", "Upstream pages fetched").unwrap();
+        let jobs = IntGaugeVec::new(
+            Opts::new("jobs", "Background jobs by status"),
+            &["status"],
+        ).unwrap();
+        let cache_dates = IntGauge::new("cache_dates", "Cached dates, in memory or spilled to disk").unwrap();
+        let cache_transactions = IntGauge::new("cache_transactions", "Cached transactions, in memory or spilled to disk").unwrap();
+        let cache_memory_dates = IntGauge::new("cache_memory_dates", "Dates held in memory").unwrap();
+        let cache_memory_bytes = IntGauge::new("cache_memory_bytes", "Measured size of the dates held in memory").unwrap();
+        let cache_spill = IntCounterVec::new(
+            Opts::new("cache_spill_total", "Dates moved between memory and the spill dir, op is evict or reload"),
+            &["op"],
+        ).unwrap();
+        let cache_persist_duration = HistogramVec::new(
+            HistogramOpts::new("cache_persist_duration_seconds", "Time to persist the cache file, result is ok or error")
+                .buckets(vec![0.005, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0]),
+            &["result"],
+        ).unwrap();
+
+        registry.register(Box::new(http_requests.clone())).unwrap();
+        registry.register(Box::new(http_duration.clone())).unwrap();
+        registry.register(Box::new(cache_lookups.clone())).unwrap();
+        registry.register(Box::new(upstream_duration.clone())).unwrap();
+        registry.register(Box::new(upstream_failures.clone())).unwrap();
+        registry.register(Box::new(upstream_pages.clone())).unwrap();
+        registry.register(Box::new(jobs.clone())).unwrap();
+        registry.register(Box::new(cache_dates.clone())).unwrap();
+        registry.register(Box::new(cache_transactions.clone())).unwrap();
+        registry.register(Box::new(cache_memory_dates.clone())).unwrap();
+        registry.register(Box::new(cache_memory_bytes.clone())).unwrap();
+        registry.register(Box::new(cache_spill.clone())).unwrap();
+        registry.register(Box::new(cache_persist_duration.clone())).unwrap();
+
+        Self {
+            registry,
+            http_requests,
+            http_duration,
+            cache_lookups,
+            upstream_duration,
+            upstream_failures,
+            upstream_pages,
+            jobs,
+            cache_dates,
+            cache_transactions,
+            cache_memory_dates,
+            cache_memory_bytes,
+            cache_spill,
+            cache_persist_duration,
+        }
+    }
+}
+
+pub struct MetricsService;
+
+impl MetricsService {
+    pub fn observe_http(method: &str, route: &str, status: u16, elapsed: Duration) {
+        METRICS.http_requests.with_label_values(&[method, route, &status.to_string()]).inc();
+        METRICS.http_duration.with_label_values(&[method, route]).observe(elapsed.as_secs_f64());
+    }
+
+    pub fn cache_lookup(hit: bool) {
+        METRICS.cache_lookups.with_label_values(&[if hit { "hit" } else { "miss" }]).inc();
+    }
+
+    pub fn observe_upstream(operation: &str, elapsed: Duration) {
+        METRICS.upstream_duration.with_label_values(&[operation]).observe(elapsed.as_secs_f64());
+    }
+
+    pub fn upstream_failure(kind: UpstreamFailure) {
+        METRICS.upstream_failures.with_label_values(&[kind.as_str()]).inc();
+    }
+
+    pub fn upstream_page_fetched() {
+        METRICS.upstream_pages.inc();
+    }
+
+    /// Tanggal dipindah ke spill (`evict`) atau di-load lagi ke memory (`reload`)
+    pub fn cache_spill(op: &str, dates: usize) {
+        METRICS.cache_spill.with_label_values(&[op]).inc_by(dates as u64);
+    }
+
+    pub fn observe_persist(ok: bool, elapsed: Duration) {
+        METRICS.cache_persist_duration
+            .with_label_values(&[if ok { "ok" } else { "error" }])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Gauge ukuran cache & job dihitung saat scrape, lalu semua metrics di-encode ke format teks Prometheus
+    pub async fn render(state: &AppState) -> String {
+        {
+            let cache = state.cache.read().await;
+            METRICS.cache_dates.set(cache.len() as i64);
+            METRICS.cache_transactions.set(cache.transactions() as i64);
+            METRICS.cache_memory_dates.set(cache.hot_len() as i64);
+            METRICS.cache_memory_bytes.set(cache.hot_bytes() as i64);
+        }
+        {
+            let jobs = state.jobs.lock().unwrap_or_else(|e| e.into_inner());
+            let (mut running, mut completed, mut failed) = (0, 0, 0);
+            for job in jobs.values() {
+                match job.status {
+                    JobStatus::Running => running += 1,
+                    JobStatus::Completed => completed += 1,
+                    JobStatus::Failed(_) => failed += 1,
+                }
+            }
+            METRICS.jobs.with_label_values(&["running"]).set(running);
+            METRICS.jobs.with_label_values(&["completed"]).set(completed);
+            METRICS.jobs.with_label_values(&["failed"]).set(failed);
+        }
+
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&METRICS.registry.gather(), &mut buffer) {
+            tracing::error!("[METRICS] Failed to encode metrics: {}", e);
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
@@ -1,9 +1,37 @@
 pub mod transaction_service;
 pub mod cache_service;
+pub mod cache_file_service;
+pub mod cache_spill_service;
 pub mod date_service;
 pub mod auth_service;
+pub mod audit_service;
+pub mod export_service;
+pub mod report_service;
+pub mod filter_service;
+pub mod stream_service;
+pub mod etag_service;
+pub mod config_service;
+pub mod metrics_service;
+pub mod health_service;
+pub mod shutdown_service;
+pub mod admin_service;
+pub mod archive_service;
 
 pub use transaction_service::*;
 pub use cache_service::*;
+pub use cache_file_service::*;
+pub use cache_spill_service::*;
 pub use date_service::*;
-pub use auth_service::*;
\ No newline at end of file
+pub use auth_service::*;
+pub use audit_service::*;
+pub use export_service::*;
+pub use report_service::*;
+pub use filter_service::*;
+pub use stream_service::*;
+pub use etag_service::*;
+pub use config_service::*;
+pub use metrics_service::*;
+pub use health_service::*;
+pub use shutdown_service::*;
+pub use admin_service::*;
+pub use archive_service::*;
\ No newline at end of file
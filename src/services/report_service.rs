@@ -0,0 +1,214 @@
+use crate::errors::DebugAppError;
+use crate::models::{Money, PdfTemplate, StoreSummary, Transaksi};
+use crate::services::{CacheService, DateService};
+use crate::state::AppState;
+use chrono::{Local, NaiveDate};
+use std::collections::HashSet;
+use printpdf::{BuiltinFont, IndirectFontRef, Line, Mm, PdfDocument, PdfLayerReference, Point};
+use tokio::fs;
+use tracing::info;
+
+// Ukuran karakter Courier: lebar 0.6 x font size (pt), 1pt = 0.3528mm
+const COURIER_WIDTH_RATIO: f32 = 0.6;
+const PT_TO_MM: f32 = 0.3528;
+
+pub struct ReportService;
+
+impl ReportService {
+    /// Load template dari `PDF_TEMPLATE_PATH` (JSON), default kalau tidak di-set
+    pub async fn load_pdf_template(template_path: Option<&str>) -> Result<PdfTemplate, DebugAppError> {
+        let Some(template_path) = template_path else {
+            return Ok(PdfTemplate::default());
+        };
+
+        let content = fs::read_to_string(&template_path).await
+            .map_err(|e| DebugAppError::InvalidInput(format!("Cannot read PDF template '{}': {}", template_path, e)))?;
+        let template: PdfTemplate = serde_json::from_str(&content)?;
+        template.validate().map_err(|problems| {
+            DebugAppError::InvalidInput(format!("Invalid PDF template '{}': {}", template_path, problems.join("; ")))
+        })?;
+        info!("[REPORT] Using PDF template from '{}'", template_path);
+        Ok(template)
+    }
+
+    /// Jumlah tanggal, transaksi dan total per store (`STORE_ID` + `STORE_IDS`) untuk range `from`/`to`,
+    /// atau seluruh cache tiap store kalau keduanya kosong. Hanya dari cache; tanggal di spill dibaca
+    /// tanpa dimasukkan lagi ke memory.
+    pub async fn store_summaries(state: &AppState, from: Option<&str>, to: Option<&str>) -> Result<Vec<StoreSummary>, DebugAppError> {
+        let mut summaries = Vec::new();
+        for store in state.config().stores() {
+            let dates = CacheService::dates_in_range(state, &store, from, to).await?;
+            let mut summary = StoreSummary {
+                store_id: store.clone(),
+                cached_dates: 0,
+                missing_dates: Vec::new(),
+                transactions: 0,
+                total_tagihan: Money::zero(),
+                parse_errors: 0,
+            };
+            let mut seen = HashSet::new();
+            CacheService::for_each_cached(state, &store, &dates, false, |date, daily| {
+                seen.insert(date.to_string());
+                summary.cached_dates += 1;
+                summary.transactions += daily.len();
+                summary.total_tagihan = summary.total_tagihan + Transaksi::total_amount(daily);
+                summary.parse_errors += Transaksi::count_parse_errors(daily);
+            })
+            .await?;
+            summary.missing_dates = dates.into_iter().filter(|date| !seen.contains(date)).collect();
+            summaries.push(summary);
+        }
+        Ok(summaries)
+    }
+
+    /// Render laporan penutupan harian: header toko/tanggal, daftar nota (waktu & nominal), total
+    pub fn render_closing_pdf(
+        date: NaiveDate,
+        store_id: &str,
+        transactions: &[Transaksi],
+        template: &PdfTemplate,
+    ) -> Result<Vec<u8>, DebugAppError> {
+        template.validate()
+            .map_err(|problems| DebugAppError::InvalidInput(format!("Invalid PDF template: {}", problems.join("; "))))?;
+
+        let mut rows: Vec<&Transaksi> = transactions.iter().collect();
+        rows.sort_by(|a, b| a.waktu_transaksi.cmp(&b.waktu_transaksi));
+
+        let line_height = template.font_size * PT_TO_MM * 1.5;
+        let chars_per_line = ((template.page_width_mm - 2.0 * template.margin_mm)
+            / (template.font_size * COURIER_WIDTH_RATIO * PT_TO_MM)) as usize;
+
+        let mut lines: Vec<String> = Vec::new();
+        lines.push(template.title.clone());
+        if let Some(store_name) = &template.store_name {
+            lines.push(store_name.clone());
+        }
+        lines.extend(template.header_lines.iter().cloned());
+        lines.push(format!("Store ID : {}", store_id));
+        lines.push(format!("Tanggal  : {}", date.format("%d/%m/%Y")));
+        lines.push(format!("Dicetak  : {}", Local::now().format("%d/%m/%Y %H:%M:%S")));
+        lines.push("-".repeat(chars_per_line));
+
+        // Kolom: No (4) | No Nota (16) | Waktu (9) | [Keterangan] | Jumlah (20, rata kanan)
+        let amount_width = 20;
+        let fixed_width = 4 + 17 + 9 + amount_width;
+        let keterangan_width = if template.show_keterangan {
+            chars_per_line.saturating_sub(fixed_width + 1)
+        } else {
+            0
+        };
+        let row_line = |no: &str, nota: &str, waktu: &str, keterangan: &str, amount: &str| {
+            let mut line = format!("{:<4}{:<17}{:<9}", no, Self::fit(nota, 16), waktu);
+            if keterangan_width > 0 {
+                line.push_str(&format!("{:<width$} ", Self::fit(keterangan, keterangan_width), width = keterangan_width));
+            }
+            line.push_str(&format!("{:>width$}", amount, width = amount_width));
+            line
+        };
+
+        lines.push(row_line("No", "No Nota", "Waktu", "Keterangan", "Jumlah"));
+        lines.push("-".repeat(chars_per_line));
+        for (i, transaksi) in rows.iter().enumerate() {
+            let waktu = DateService::parse_datetime(&transaksi.waktu_transaksi)
+                .map(|dt| dt.format("%H:%M:%S").to_string())
+                .unwrap_or_else(|| Self::fit(&transaksi.waktu_transaksi, 8));
+            lines.push(row_line(
+                &(i + 1).to_string(),
+                &transaksi.no_nota,
+                &waktu,
+                &transaksi.keterangan,
+                &transaksi.total_tagihan.format_id(),
+            ));
+        }
+        lines.push("-".repeat(chars_per_line));
+        lines.push(format!("Jumlah Transaksi : {}", rows.len()));
+        lines.push(format!("Total Penjualan  : {}", Transaksi::total_amount(transactions).format_id()));
+        let parse_errors = Transaksi::count_parse_errors(transactions);
+        if parse_errors > 0 {
+            lines.push(format!("Nominal gagal dibaca : {} transaksi", parse_errors));
+        }
+        if !template.footer_lines.is_empty() {
+            lines.push(String::new());
+            lines.extend(template.footer_lines.iter().cloned());
+        }
+
+        let title = format!("{} {}", template.title, date.format("%d/%m/%Y"));
+        let (doc, page, layer) = PdfDocument::new(
+            title,
+            Mm(template.page_width_mm),
+            Mm(template.page_height_mm),
+            "Layer 1",
+        );
+        let font = doc.add_builtin_font(BuiltinFont::Courier)
+            .map_err(|e| DebugAppError::Serialization(format!("PDF font error: {}", e)))?;
+        let bold = doc.add_builtin_font(BuiltinFont::CourierBold)
+            .map_err(|e| DebugAppError::Serialization(format!("PDF font error: {}", e)))?;
+
+        let mut current_layer = doc.get_page(page).get_layer(layer);
+        let mut y = template.page_height_mm - template.margin_mm;
+        for (i, line) in lines.iter().enumerate() {
+            if y < template.margin_mm {
+                let (next_page, next_layer) = doc.add_page(
+                    Mm(template.page_width_mm),
+                    Mm(template.page_height_mm),
+                    "Layer 1",
+                );
+                current_layer = doc.get_page(next_page).get_layer(next_layer);
+                y = template.page_height_mm - template.margin_mm;
+            }
+
+            if line.chars().all(|c| c == '-') && !line.is_empty() {
+                Self::draw_rule(&current_layer, template, y + line_height / 3.0);
+            } else {
+                let line_font: &IndirectFontRef = if i == 0 { &bold } else { &font };
+                current_layer.use_text(line.as_str(), template.font_size, Mm(template.margin_mm), Mm(y), line_font);
+            }
+            y -= line_height;
+        }
+
+        doc.save_to_bytes()
+            .map_err(|e| DebugAppError::Serialization(format!("PDF error: {}", e)))
+    }
+
+    fn draw_rule(layer: &PdfLayerReference, template: &PdfTemplate, y: f32) {
+        layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(template.margin_mm), Mm(y)), false),
+                (Point::new(Mm(template.page_width_mm - template.margin_mm), Mm(y)), false),
+            ],
+            is_closed: false,
+        });
+    }
+
+    /// Potong teks agar muat di kolom (Courier, satu karakter satu kolom)
+    fn fit(text: &str, width: usize) -> String {
+        if text.chars().count() <= width {
+            return text.to_string();
+        }
+        let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+        truncated.push('~');
+        truncated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_rejects_invalid_template_instead_of_panicking() {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let template = PdfTemplate { font_size: 0.0, ..PdfTemplate::default() };
+        let result = ReportService::render_closing_pdf(date, "1", &[], &template);
+        assert!(matches!(result, Err(DebugAppError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn load_rejects_invalid_template_file() {
+        let path = std::env::temp_dir().join(format!("pdf_template_{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"page_width_mm": 20.0, "margin_mm": 15.0}"#).unwrap();
+        let result = ReportService::load_pdf_template(path.to_str()).await;
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(result, Err(DebugAppError::InvalidInput(message)) if message.contains("page_width_mm")));
+    }
+}
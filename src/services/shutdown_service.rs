@@ -0,0 +1,112 @@
+use crate::services::CacheService;
+use crate::state::AppState;
+use std::future::IntoFuture;
+use std::io;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::time::{sleep, timeout_at, Duration, Instant};
+use tracing::{error, info, warn};
+
+const JOB_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+pub struct ShutdownService;
+
+impl ShutdownService {
+    /// Tunggu SIGTERM (`docker stop`) atau SIGINT (Ctrl+C), lalu tandai state shutting down.
+    /// Dipakai sebagai sinyal `with_graceful_shutdown`, jadi server langsung berhenti menerima request baru.
+    pub async fn wait_for_signal(state: AppState) {
+        let terminate = async {
+            match signal(SignalKind::terminate()) {
+                Ok(mut terminate) => {
+                    terminate.recv().await;
+                }
+                Err(e) => {
+                    error!("[SHUTDOWN] Cannot listen for SIGTERM: {}", e);
+                    std::future::pending::<()>().await;
+                }
+            }
+        };
+
+        tokio::select! {
+            _ = terminate => info!("[SHUTDOWN] SIGTERM received"),
+            result = tokio::signal::ctrl_c() => match result {
+                Ok(()) => info!("[SHUTDOWN] SIGINT received"),
+                Err(e) => {
+                    error!("[SHUTDOWN] Cannot listen for SIGINT: {}", e);
+                    std::future::pending::<()>().await;
+                }
+            },
+        }
+        state.begin_shutdown();
+    }
+
+    /// Jalankan server sampai sinyal shutdown, lalu drain request in-flight dan job background
+    /// maksimal `SHUTDOWN_TIMEOUT_SECS` (satu deadline untuk keduanya), dan terakhir save cache ke file
+    pub async fn run<S>(state: &AppState, server: S)
+    where
+        S: IntoFuture<Output = io::Result<()>>,
+    {
+        let server = server.into_future();
+        tokio::pin!(server);
+
+        // `biased`: saat sinyal datang server juga langsung selesai kalau tidak ada request in-flight,
+        // jalur shutdown harus tetap dipilih supaya job ikut di-drain
+        let stopped_by_signal = tokio::select! {
+            biased;
+            _ = state.shutdown_requested() => true,
+            result = &mut server => {
+                Self::log_server_result(result);
+                false
+            }
+        };
+
+        let timeout_secs = state.config().server.shutdown_timeout_secs;
+        let deadline = Instant::now() + Duration::from_secs(timeout_secs);
+        info!(timeout_secs, "[SHUTDOWN] Draining in-flight requests and jobs");
+        // Job background berhenti di batas tanggal berikutnya juga saat server berhenti karena error
+        state.begin_shutdown();
+
+        if stopped_by_signal {
+            match timeout_at(deadline, &mut server).await {
+                Ok(result) => Self::log_server_result(result),
+                Err(_) => warn!("[SHUTDOWN] Timed out waiting for in-flight requests, exiting anyway"),
+            }
+        }
+
+        loop {
+            let running = state.running_job_count();
+            if running == 0 {
+                break;
+            }
+            if Instant::now() >= deadline {
+                warn!(running, "[SHUTDOWN] Timed out waiting for background jobs");
+                break;
+            }
+            sleep(JOB_POLL_INTERVAL).await;
+        }
+
+        Self::final_save(state).await;
+    }
+
+    fn log_server_result(result: io::Result<()>) {
+        match result {
+            Ok(()) => info!("[SHUTDOWN] Server stopped accepting requests, in-flight requests finished"),
+            Err(e) => error!("[SHUTDOWN] Server stopped with error: {}", e),
+        }
+    }
+
+    /// Save terakhir supaya data yang di-fetch sejak batch save terakhir tidak hilang.
+    /// Cache kosong tidak ditulis, supaya file yang gagal di-load saat startup tidak tertimpa.
+    async fn final_save(state: &AppState) {
+        let (empty, dates) = {
+            let cache = state.cache.read().await;
+            (cache.is_empty(), cache.len())
+        };
+        if empty {
+            info!("[SHUTDOWN] Cache empty, skipping final save");
+            return;
+        }
+        if CacheService::save_cache_to_file(state).await.is_ok() {
+            info!(dates, "[SHUTDOWN] Final cache save completed");
+        }
+    }
+}
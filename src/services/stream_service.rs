@@ -0,0 +1,137 @@
+use crate::models::{ExportQuery, StreamMode, Transaksi};
+use crate::services::{CacheService, RowFilter};
+use crate::state::AppState;
+use axum::body::Body;
+use axum::http::{header, HeaderMap};
+use futures_util::stream;
+use std::convert::Infallible;
+use tracing::{error, info};
+
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+pub struct StreamService;
+
+/// Posisi stream: tanggal berikutnya yang dibaca dari cache
+struct StreamCursor {
+    state: AppState,
+    store: String,
+    dates: std::vec::IntoIter<String>,
+    uncached: Option<Vec<Transaksi>>,
+    filter: RowFilter,
+    mode: StreamMode,
+    opened: bool,
+    first: bool,
+    rows: usize,
+    finished: bool,
+}
+
+impl StreamService {
+    /// `?stream=ndjson|array` menang; `Accept: application/x-ndjson` juga mengaktifkan NDJSON
+    pub fn requested_mode(headers: &HeaderMap, query: &ExportQuery) -> Option<StreamMode> {
+        if query.stream.is_some() {
+            return query.stream;
+        }
+
+        let accept = headers
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("");
+        accept.contains(NDJSON_CONTENT_TYPE).then_some(StreamMode::Ndjson)
+    }
+
+    pub fn content_type(mode: StreamMode) -> &'static str {
+        match mode {
+            StreamMode::Ndjson => NDJSON_CONTENT_TYPE,
+            StreamMode::Array => "application/json",
+        }
+    }
+
+    /// Body yang membaca cache satu tanggal per chunk. Read lock hanya dipegang selama satu
+    /// tanggal di-serialize, jadi memory maksimal kira-kira sebesar data satu hari.
+    /// Dedup berlaku per tanggal (index `DailyTransactions`), tidak lintas tanggal.
+    pub fn cache_body(
+        state: AppState,
+        store: String,
+        dates: Vec<String>,
+        uncached: Vec<Transaksi>,
+        filter: RowFilter,
+        mode: StreamMode,
+    ) -> Body {
+        let cursor = StreamCursor {
+            state,
+            store,
+            dates: dates.into_iter(),
+            uncached: (!uncached.is_empty()).then_some(uncached),
+            filter,
+            mode,
+            opened: false,
+            first: true,
+            rows: 0,
+            finished: false,
+        };
+
+        let chunks = stream::unfold(cursor, |mut cursor| async move {
+            if cursor.finished {
+                return None;
+            }
+
+            let mut chunk = Vec::new();
+            if !cursor.opened && cursor.mode == StreamMode::Array {
+                chunk.push(b'[');
+            }
+            cursor.opened = true;
+
+            if let Some(date) = cursor.dates.next() {
+                let (state, store) = (cursor.state.clone(), cursor.store.clone());
+                let dates = [date];
+                let read = CacheService::for_each_cached(&state, &store, &dates, true, |_, daily| {
+                    Self::write_rows(&mut cursor, &mut chunk, daily.iter());
+                })
+                .await;
+                if let Err(e) = read {
+                    error!(date = %dates[0], error = ?e, "[STREAM] Failed to read spilled date, skipping");
+                }
+            } else {
+                if let Some(uncached) = cursor.uncached.take() {
+                    Self::write_rows(&mut cursor, &mut chunk, uncached.iter());
+                }
+                if cursor.mode == StreamMode::Array {
+                    chunk.push(b']');
+                }
+                cursor.finished = true;
+                info!("[STREAM] Sent {} transactions", cursor.rows);
+            }
+
+            Some((Ok::<_, Infallible>(chunk), cursor))
+        });
+
+        Body::from_stream(chunks)
+    }
+
+    fn write_rows<'a>(cursor: &mut StreamCursor, chunk: &mut Vec<u8>, rows: impl Iterator<Item = &'a Transaksi>) {
+        for transaksi in rows.filter(|t| cursor.filter.matches(t)) {
+            let line = match serde_json::to_vec(transaksi) {
+                Ok(line) => line,
+                Err(e) => {
+                    error!("[STREAM] Failed to serialize {}: {}", transaksi.no_nota, e);
+                    continue;
+                }
+            };
+
+            match cursor.mode {
+                StreamMode::Ndjson => {
+                    chunk.extend_from_slice(&line);
+                    chunk.push(b'\n');
+                }
+                StreamMode::Array => {
+                    if !cursor.first {
+                        chunk.push(b',');
+                    }
+                    chunk.extend_from_slice(&line);
+                }
+            }
+            cursor.first = false;
+            cursor.rows += 1;
+        }
+    }
+}
@@ -1,17 +1,21 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::env;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex as StdMutex, RwLock as StdRwLock};
+use tokio::sync::{watch, Mutex, RwLock};
 use tokio::time::Instant;
-use crate::models::{Transaksi, Payload};
+use chrono::{DateTime, Utc};
+use crate::config::Config;
+use crate::errors::DebugAppError;
+use crate::models::{CacheStore, ChangeLogEntry, Payload};
 use tracing::{info, warn};
 // Removed unused serde imports
 
+/// Fetch upstream yang sedang/sudah berjalan (`AppState::start_job`), dipakai readiness, metrics dan drain shutdown
 #[derive(Debug, Clone)]
 pub struct JobInfo {
-    #[allow(dead_code)]
-    pub payload: Payload,
     pub start_time: Instant,
     pub status: JobStatus,
 }
@@ -23,168 +27,443 @@ pub enum JobStatus {
     Failed(String),
 }
 
+/// Hasil load cache file saat startup
+#[derive(Debug, Clone, Default)]
+pub enum CacheLoadStatus {
+    #[default]
+    Pending,
+    Loaded,
+    Failed(String),
+}
+
+/// Sinyal yang dipakai `/health/ready`; diisi oleh service saat load cache, fetch upstream dan persist
+#[derive(Debug, Clone, Default)]
+pub struct HealthState {
+    pub cache_load: CacheLoadStatus,
+    pub last_upstream_success: Option<DateTime<Utc>>,
+    /// Per akun upstream: kapan sesinya terakhir ditolak; dihapus setelah request berikutnya berhasil
+    pub sessions_rejected: HashMap<String, DateTime<Utc>>,
+    pub last_persist_success: Option<DateTime<Utc>>,
+    /// Error persist terakhir; `None` setelah save berikutnya berhasil
+    pub persist_error: Option<(DateTime<Utc>, String)>,
+}
+
+/// Tandai job selesai saat di-drop (`AppState::start_job`). Tanpa `finish()` (request dibatalkan,
+/// panic) job dicatat gagal, jadi tidak pernah tertinggal `Running`.
+pub struct JobGuard {
+    jobs: Arc<StdMutex<HashMap<String, JobInfo>>>,
+    job_id: String,
+    status: JobStatus,
+}
+
+impl JobGuard {
+    pub fn id(&self) -> &str {
+        &self.job_id
+    }
+
+    /// Catat hasil job; status dipasang saat guard di-drop
+    pub fn finish<T>(mut self, result: &Result<T, DebugAppError>) {
+        self.status = match result {
+            Ok(_) => JobStatus::Completed,
+            Err(e) => JobStatus::Failed(e.message()),
+        };
+    }
+}
+
+impl Drop for JobGuard {
+    fn drop(&mut self) {
+        let status = std::mem::replace(&mut self.status, JobStatus::Completed);
+        if let JobStatus::Failed(reason) = &status {
+            warn!(job_id = %self.job_id, "[JOB] Failed: {}", reason);
+        }
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(job) = jobs.get_mut(&self.job_id) {
+            job.status = status;
+        }
+    }
+}
+
+/// Lepas tanda admin operation saat di-drop (`AppState::start_admin_operation`)
+pub struct AdminOperationGuard {
+    operations: Arc<StdMutex<HashSet<String>>>,
+    operation: String,
+}
+
+impl Drop for AdminOperationGuard {
+    fn drop(&mut self) {
+        self.operations.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.operation);
+        info!("[ADMIN] Operation '{}' completed", self.operation);
+    }
+}
+
 #[derive(Clone)]
 pub struct AppState {
-    pub cache: Arc<RwLock<HashMap<String, Vec<Transaksi>>>>, // Key: tanggal, Value: transaksi di tanggal itu
-    pub processing: Arc<RwLock<HashMap<String, bool>>>, // Key: tanggal yang sedang diproses
-    pub jobs: Arc<RwLock<HashMap<String, JobInfo>>>, // Key: job_id, Value: job info
-    pub active_jobs_count: Arc<AtomicBool>, // Simple flag untuk backward compatibility
-    pub admin_operations: Arc<RwLock<HashMap<String, bool>>>, // Track running admin operations
-    pub unauthorized_state: Arc<RwLock<bool>>, // Track unauthorized state
+    pub cache: Arc<RwLock<CacheStore>>, // Per store & tanggal: transaksi di memory (LRU, dibatasi CACHE_MEMORY_BUDGET_MB) atau di file spill
+    pub spill_lock: Arc<Mutex<()>>, // Evict ke spill dan bersih-bersih spill stale berjalan satu per satu, tulis file tanpa lock cache
+    pub processing: Arc<RwLock<HashMap<String, bool>>>, // Key: `store|tanggal` yang sedang diproses (`processing_key`)
+    pub jobs: Arc<StdMutex<HashMap<String, JobInfo>>>, // Key: job_id, fetch upstream (smart fetch, force refresh); lock hanya sebentar
+    pub job_sequence: Arc<AtomicU64>, // Nomor urut job_id, request range yang sama tetap tercatat terpisah
+    pub admin_operations: Arc<StdMutex<HashSet<String>>>, // Admin operation yang sedang jalan, dilepas oleh `AdminOperationGuard`
+    pub unauthorized_state: Arc<RwLock<HashMap<String, bool>>>, // Per akun upstream; akun yang belum tercatat dianggap unauthorized
+    pub audit_log: Arc<RwLock<Vec<ChangeLogEntry>>>, // Perubahan yang terdeteksi saat force-refresh, maksimal AUDIT_LOG_MAX_ENTRIES terbaru
+    pub session_cookie: Arc<RwLock<HashMap<String, String>>>, // Per akun upstream: cookie milik server untuk read API (GET /transactions)
+    pub config: Arc<StdRwLock<Arc<Config>>>, // Konfigurasi aktif, diganti utuh saat hot-reload
+    pub health: Arc<RwLock<HealthState>>, // Status untuk readiness check
+    pub shutdown: Arc<watch::Sender<bool>>, // `true` setelah SIGTERM/SIGINT, job berhenti di batas tanggal berikutnya
 }
 
 impl AppState {
     // Cleanup stuck processing flags (untuk recovery)
-    pub async fn cleanup_stuck_processing(&self) {
+    pub async fn cleanup_stuck_processing(&self) -> usize {
         let mut processing_write = self.processing.write().await;
+        let cleared = processing_write.len();
         processing_write.clear();
-        info!("[CLEANUP] Cleared all stuck processing flags");
+        info!("[CLEANUP] Cleared all stuck processing flags ({})", cleared);
+        cleared
     }
-    pub fn new() -> Self {
+    pub fn new(config: Config) -> Self {
         Self {
-            cache: Arc::new(RwLock::new(HashMap::new())),
+            cache: Arc::new(RwLock::new(CacheStore::default())),
+            spill_lock: Arc::new(Mutex::new(())),
             processing: Arc::new(RwLock::new(HashMap::new())),
-            jobs: Arc::new(RwLock::new(HashMap::new())),
-            active_jobs_count: Arc::new(AtomicBool::new(false)),
-            admin_operations: Arc::new(RwLock::new(HashMap::new())),
-            unauthorized_state: Arc::new(RwLock::new(true)),
+            jobs: Arc::new(StdMutex::new(HashMap::new())),
+            job_sequence: Arc::new(AtomicU64::new(0)),
+            admin_operations: Arc::new(StdMutex::new(HashSet::new())),
+            unauthorized_state: Arc::new(RwLock::new(HashMap::new())),
+            audit_log: Arc::new(RwLock::new(Vec::new())),
+            session_cookie: Arc::new(RwLock::new(HashMap::new())),
+            config: Arc::new(StdRwLock::new(Arc::new(config))),
+            health: Arc::new(RwLock::new(HealthState::default())),
+            shutdown: Arc::new(watch::Sender::new(false)),
         }
     }
 
+    /// Snapshot config saat ini; simpan di variabel lokal kalau dipakai berkali-kali dalam satu operasi
+    pub fn config(&self) -> Arc<Config> {
+        self.config.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Pasang config hasil reload (sync lock, hanya memegang lock selama swap `Arc`)
+    pub fn set_config(&self, config: Config) {
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(config);
+    }
+
+    /// Load file backup setiap store (`STORE_ID` + `STORE_IDS`). Store yang gagal tidak menghalangi
+    /// store lain, kecuali `CACHE_STRICT_LOAD` aktif.
     pub async fn load_cache_from_file(&self) -> Result<(), Box<dyn std::error::Error>> {
-        use crate::services::CacheService;
-        
-        // Clean empty entries from file first
-        match CacheService::clean_empty_cache_entries(self).await {
-            Ok(cleaned) => {
-                if cleaned > 0 {
-                    info!("[STARTUP] Cleaned {} empty cache entries from file", cleaned);
+        use crate::services::{AuditService, CacheFileService, CacheService};
+
+        let config = self.config();
+        let mut failures = Vec::new();
+        for store in config.stores() {
+            let e = match CacheService::load_store_from_file_cache(self, &store).await {
+                Ok(count) => {
+                    if count > 0 {
+                        info!(store = %store, "[STARTUP] Loaded {} dates from backup file", count);
+                    } else {
+                        info!(store = %store, "[STARTUP] No backup file found, starting with empty cache");
+                    }
+                    continue;
                 }
+                Err(e) => e,
+            };
+            // Pesan diberi id store kalau ada lebih dari satu store
+            let message = if config.stores().len() > 1 {
+                format!("store {}: {}", store, e.message())
+            } else {
+                e.message()
+            };
+            if config.cache.strict_load {
+                self.health.write().await.cache_load = CacheLoadStatus::Failed(message.clone());
+                return Err(message.into());
             }
-            Err(e) => {
-                warn!("[STARTUP] Failed to clean empty entries: {:?}", e);
-            }
-        }
-        
-        // Then load cleaned cache to memory
-        match CacheService::load_all_from_file_cache(self).await {
-            Ok(count) => {
-                if count > 0 {
-                    info!("[STARTUP] Loaded {} dates from backup file", count);
-                } else {
-                    info!("[STARTUP] No backup file found, starting with empty cache");
+
+            // File disisihkan supaya save berikutnya tidak menimpa history yang belum bisa dibaca
+            let path = CacheService::cache_file_path(&config, &store);
+            failures.push(match CacheFileService::quarantine(&path).await {
+                Ok(Some(moved_to)) => {
+                    warn!(store = %store, moved_to = %moved_to, "[STARTUP] Failed to load cache, starting empty: {}", e.message());
+                    format!("{} (moved to {})", message, moved_to)
                 }
-            }
-            Err(e) => {
-                warn!("[STARTUP] Failed to load cache: {:?}", e);
-            }
+                Ok(None) => {
+                    warn!(store = %store, "[STARTUP] Failed to load cache: {}", e.message());
+                    message
+                }
+                Err(move_error) => {
+                    warn!(store = %store, "[STARTUP] Failed to load cache and could not move it aside: {}; {}", e.message(), move_error.message());
+                    message
+                }
+            });
+        }
+        self.health.write().await.cache_load = if failures.is_empty() {
+            CacheLoadStatus::Loaded
+        } else {
+            CacheLoadStatus::Failed(failures.join("; "))
+        };
+
+        if let Err(e) = AuditService::load_from_file(self).await {
+            warn!("[STARTUP] Failed to load audit log: {:?}", e);
         }
         
         Ok(())
     }
 
+    /// Load untuk `cache export` di CLI: tidak memindah, menulis ulang atau spill file apa pun, dan
+    /// gagal pada store pertama yang tidak bisa di-load
+    pub async fn load_cache_read_only(&self) -> Result<(), String> {
+        use crate::services::CacheService;
+
+        let config = self.config();
+        for store in config.stores() {
+            let count = CacheService::load_store_read_only(self, &store)
+                .await
+                .map_err(|e| format!("store {}: {}", store, e.message()))?;
+            info!(store = %store, "[STARTUP] Loaded {} dates (read-only)", count);
+        }
+        self.health.write().await.cache_load = CacheLoadStatus::Loaded;
+        Ok(())
+    }
+
+    /// Key map `processing`: tanggal yang sama di store berbeda diproses terpisah
+    pub fn processing_key(store: &str, date: &str) -> String {
+        format!("{}|{}", store, date)
+    }
+
     pub fn generate_job_id(payload: &Payload) -> String {
         format!("{}-{}", payload.from, payload.to)
     }
 
-    pub async fn start_job(&self, payload: Payload) -> Result<String, String> {
-        let job_id = Self::generate_job_id(&payload);
-        let mut jobs = self.jobs.write().await;
-        
-        // Cek apakah job dengan range yang sama sudah berjalan
-        if let Some(existing_job) = jobs.get(&job_id) {
-            if matches!(existing_job.status, JobStatus::Running)
-                && existing_job.start_time.elapsed().as_secs() < 300 {
-                return Err("Job already running for this range".to_string());
-            }
+    /// Catat fetch upstream yang sedang berjalan untuk `store`; selesai saat guard di-drop.
+    /// Dipakai `/health/ready` (`jobs.running`), metrics `jobs` dan drain saat shutdown.
+    /// `ServiceBusy` kalau job `Running` sudah mencapai `MAX_CONCURRENT_JOBS`.
+    pub fn start_job(&self, store: &str, payload: &Payload) -> Result<JobGuard, DebugAppError> {
+        let max_jobs = self.config().cache.max_concurrent_jobs;
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        let running = jobs.values().filter(|job| matches!(job.status, JobStatus::Running)).count();
+        if running >= max_jobs {
+            warn!(store = %store, running, max_jobs, "[JOB] Rejected - too many running jobs");
+            return Err(DebugAppError::ServiceBusy(format!(
+                "Too many upstream fetches running ({}/{}), retry later",
+                running, max_jobs
+            )));
         }
-        
-        // Limit concurrent jobs from env variable
-        let max_concurrent_jobs: usize = env::var("MAX_CONCURRENT_JOBS")
-            .unwrap_or_else(|_| "3".to_string())
-            .parse().unwrap_or(3);
-        let running_jobs = jobs.values().filter(|j| matches!(j.status, JobStatus::Running)).count();
-        if running_jobs >= max_concurrent_jobs {
-            return Err("Too many concurrent jobs".to_string());
-        }
-        
-        jobs.insert(job_id.clone(), JobInfo {
-            payload,
+        let sequence = self.job_sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        let job_id = format!("{}|{}#{}", store, Self::generate_job_id(payload), sequence);
+        let job = JobInfo {
             start_time: Instant::now(),
             status: JobStatus::Running,
-        });
-        
-        self.active_jobs_count.store(true, Ordering::Relaxed);
-        Ok(job_id)
+        };
+        jobs.insert(job_id.clone(), job);
+        Ok(JobGuard {
+            jobs: self.jobs.clone(),
+            job_id,
+            status: JobStatus::Failed("cancelled".to_string()),
+        })
     }
 
-    pub async fn complete_job(&self, job_id: &str, status: JobStatus) {
-        let mut jobs = self.jobs.write().await;
-        if let Some(job) = jobs.get_mut(job_id) {
-            job.status = status;
-        }
-        
-        // Update active jobs flag
-        let has_running = jobs.values().any(|j| matches!(j.status, JobStatus::Running));
-        self.active_jobs_count.store(has_running, Ordering::Relaxed);
-    }
-
-    pub async fn get_job_status(&self, job_id: &str) -> Option<JobStatus> {
-        let jobs = self.jobs.read().await;
-        jobs.get(job_id).map(|j| j.status.clone())
+    /// Jalankan `job` sebagai job tercatat (`start_job`), dibatalkan dengan `Timeout` setelah
+    /// `JOB_TIMEOUT_SECS`. Tanggal yang sudah di-merge sebelum timeout tetap di cache.
+    pub async fn run_job<T, F>(&self, store: &str, payload: &Payload, job: F) -> Result<T, DebugAppError>
+    where
+        F: Future<Output = Result<T, DebugAppError>>,
+    {
+        let guard = self.start_job(store, payload)?;
+        let timeout_secs = self.config().cache.job_timeout_secs;
+        info!(job_id = %guard.id(), "[JOB] Started");
+        let result = match tokio::time::timeout(Duration::from_secs(timeout_secs), job).await {
+            Ok(result) => result,
+            Err(_) => Err(DebugAppError::Timeout(format!(
+                "Upstream fetch did not finish within {}s",
+                timeout_secs
+            ))),
+        };
+        guard.finish(&result);
+        result
     }
 
+    /// Hapus job yang sudah selesai lebih dari 1 jam; job `Running` dilepas oleh guard-nya sendiri
     pub async fn cleanup_old_jobs(&self) {
-        let mut jobs = self.jobs.write().await;
-        jobs.retain(|_, job| {
-            match job.status {
-                JobStatus::Running => job.start_time.elapsed().as_secs() < 300,
-                _ => job.start_time.elapsed().as_secs() < 3600, // Keep completed jobs for 1 hour
-            }
-        });
-        
+        self.jobs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|_, job| matches!(job.status, JobStatus::Running) || job.start_time.elapsed().as_secs() < 3600);
+
         // Cleanup stuck processing flags juga
-        drop(jobs);
         self.cleanup_stuck_processing().await;
     }
 
-    /// Check if admin operation can be started (only one instance allowed)
-    pub async fn start_admin_operation(&self, operation: &str) -> bool {
-        let mut ops = self.admin_operations.write().await;
-        
-        // Check if operation is already running
-        if ops.get(operation).unwrap_or(&false) == &true {
+    /// Tandai admin operation sedang jalan (hanya satu instance). `None` kalau sudah jalan;
+    /// tanda dilepas saat guard di-drop, termasuk kalau request dibatalkan atau panic.
+    pub fn start_admin_operation(&self, operation: &str) -> Option<AdminOperationGuard> {
+        let mut ops = self.admin_operations.lock().unwrap_or_else(|e| e.into_inner());
+        if !ops.insert(operation.to_string()) {
             info!("[ADMIN] Operation '{}' blocked - already running", operation);
-            return false;
+            return None;
         }
-        
-        // Mark operation as running
-        ops.insert(operation.to_string(), true);
         info!("[ADMIN] Operation '{}' started", operation);
-        true
-    }
-    
-    /// Mark admin operation as completed
-    pub async fn complete_admin_operation(&self, operation: &str) {
-        let mut ops = self.admin_operations.write().await;
-        ops.insert(operation.to_string(), false);
-        info!("[ADMIN] Operation '{}' completed", operation);
+        Some(AdminOperationGuard {
+            operations: self.admin_operations.clone(),
+            operation: operation.to_string(),
+        })
     }
 
-    /// Set unauthorized state
-    pub async fn set_unauthorized(&self, unauthorized: bool) {
+    /// Set unauthorized state akun upstream
+    pub async fn set_unauthorized(&self, account: &str, unauthorized: bool) {
         let mut state = self.unauthorized_state.write().await;
-        *state = unauthorized;
+        state.insert(account.to_string(), unauthorized);
+        let mut health = self.health.write().await;
         if unauthorized {
-            info!("[AUTH] Unauthorized state set - future requests will be rejected");
+            health.sessions_rejected.insert(account.to_string(), Utc::now());
+            info!(account = %account, "[AUTH] Unauthorized state set - future requests will be rejected");
         } else {
-            info!("[AUTH] Unauthorized state cleared");
+            health.sessions_rejected.remove(account);
+            info!(account = %account, "[AUTH] Unauthorized state cleared");
         }
     }
 
-    /// Check if currently unauthorized
-    pub async fn is_unauthorized(&self) -> bool {
+    /// Check if akun upstream currently unauthorized (belum pernah login juga dihitung unauthorized)
+    pub async fn is_unauthorized(&self, account: &str) -> bool {
         let state = self.unauthorized_state.read().await;
-        *state
+        state.get(account).copied().unwrap_or(true)
     }
-}
\ No newline at end of file
+
+    /// Simpan (atau hapus) cookie sesi upstream yang dipakai server untuk satu akun
+    pub async fn set_session_cookie(&self, account: &str, cookie: Option<String>) {
+        let mut sessions = self.session_cookie.write().await;
+        match cookie {
+            Some(cookie) => {
+                sessions.insert(account.to_string(), cookie);
+            }
+            None => {
+                if sessions.remove(account).is_some() {
+                    info!(account = %account, "[AUTH] Server session cookie cleared");
+                }
+            }
+        }
+    }
+
+    /// Cookie sesi upstream milik server untuk akun ini, kalau sudah login
+    pub async fn session_cookie(&self, account: &str) -> Option<String> {
+        self.session_cookie.read().await.get(account).cloned()
+    }
+
+    /// Akun yang sesinya sedang dipegang server
+    pub async fn session_accounts(&self) -> Vec<String> {
+        let mut accounts: Vec<String> = self.session_cookie.read().await.keys().cloned().collect();
+        accounts.sort();
+        accounts
+    }
+
+    /// Tandai server sedang shutdown; job background berhenti sebelum tanggal berikutnya
+    pub fn begin_shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        *self.shutdown.borrow()
+    }
+
+    /// Selesai saat `begin_shutdown()` dipanggil
+    pub async fn shutdown_requested(&self) {
+        let mut receiver = self.shutdown.subscribe();
+        let _ = receiver.wait_for(|shutting_down| *shutting_down).await;
+    }
+
+    pub fn running_job_count(&self) -> usize {
+        self.jobs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .filter(|job| matches!(job.status, JobStatus::Running))
+            .count()
+    }
+
+    /// Catat fetch upstream yang berhasil (untuk readiness)
+    pub async fn record_upstream_success(&self) {
+        self.health.write().await.last_upstream_success = Some(Utc::now());
+    }
+
+    /// Catat hasil persist cache file (untuk readiness)
+    pub async fn record_persist(&self, error: Option<String>) {
+        let mut health = self.health.write().await;
+        match error {
+            Some(message) => health.persist_error = Some((Utc::now(), message)),
+            None => {
+                health.last_persist_success = Some(Utc::now());
+                health.persist_error = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload() -> Payload {
+        Payload { from: "01/10/2025".to_string(), to: "02/10/2025".to_string(), cookie: "secret".to_string() }
+    }
+
+    fn status(state: &AppState, job_id: &str) -> JobStatus {
+        state.jobs.lock().unwrap()[job_id].status.clone()
+    }
+
+    #[test]
+    fn job_guard_tracks_running_and_result() {
+        let state = AppState::new(Config::default());
+        let first = state.start_job("264", &payload()).unwrap();
+        let second = state.start_job("264", &payload()).unwrap();
+        let (first_id, second_id) = (first.id().to_string(), second.id().to_string());
+        assert_ne!(first_id, second_id);
+        assert_eq!(state.running_job_count(), 2);
+        assert!(first_id.starts_with("264|01/10/2025-02/10/2025#"), "{}", first_id);
+
+        first.finish::<()>(&Ok(()));
+        assert!(matches!(status(&state, &first_id), JobStatus::Completed));
+        second.finish::<()>(&Err(DebugAppError::Unauthorized("expired".to_string())));
+        assert!(matches!(status(&state, &second_id), JobStatus::Failed(reason) if reason == "expired"));
+        assert_eq!(state.running_job_count(), 0);
+    }
+
+    #[test]
+    fn dropped_job_is_recorded_as_cancelled() {
+        let state = AppState::new(Config::default());
+        let job = state.start_job("264", &payload()).unwrap();
+        let job_id = job.id().to_string();
+        drop(job);
+        assert!(matches!(status(&state, &job_id), JobStatus::Failed(reason) if reason == "cancelled"));
+        assert_eq!(state.running_job_count(), 0);
+    }
+
+    #[test]
+    fn start_job_rejects_above_max_concurrent_jobs() {
+        let mut config = Config::default();
+        config.cache.max_concurrent_jobs = 1;
+        let state = AppState::new(config);
+        let first = state.start_job("264", &payload()).unwrap();
+        let rejected = state.start_job("264", &payload()).err().unwrap();
+        assert_eq!(rejected.status(), axum::http::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(state.running_job_count(), 1);
+
+        first.finish::<()>(&Ok(()));
+        assert!(state.start_job("264", &payload()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn run_job_times_out_and_records_failure() {
+        let mut config = Config::default();
+        config.cache.job_timeout_secs = 1;
+        let state = AppState::new(config);
+        let result = state
+            .run_job("264", &payload(), async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(())
+            })
+            .await;
+        let error = result.err().unwrap();
+        assert_eq!(error.status(), axum::http::StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(state.running_job_count(), 0);
+        let jobs = state.jobs.lock().unwrap();
+        assert!(jobs.values().all(|job| matches!(&job.status, JobStatus::Failed(reason) if reason.contains("1s"))));
+    }
+}
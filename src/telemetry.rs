@@ -0,0 +1,87 @@
+use crate::config::{Config, LogFormat, TelemetryConfig};
+use crate::services::LogLevelHandle;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing::{info, warn};
+use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter};
+
+const OTLP_TRACES_PATH: &str = "/v1/traces";
+
+/// Logging + (opsional) ekspor trace OTLP. Simpan sampai shutdown supaya span terakhir ikut terkirim.
+pub struct Telemetry {
+    pub log_handle: LogLevelHandle,
+    tracer_provider: Option<SdkTracerProvider>,
+}
+
+impl Telemetry {
+    pub fn init(config: &Config) -> Self {
+        // Setup logging with config log level, filter bisa diganti saat hot-reload
+        let (log_filter, log_handle) = reload::Layer::new(
+            EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| config.server.log_level.clone().into()),
+        );
+
+        let (tracer_provider, otlp_error) = match Self::otlp_provider(&config.telemetry) {
+            Ok(provider) => (provider, None),
+            Err(e) => (None, Some(e)),
+        };
+        let otel_layer = tracer_provider.as_ref().map(|provider| {
+            tracing_opentelemetry::layer().with_tracer(provider.tracer(config.telemetry.service_name.clone()))
+        });
+
+        // LOG_FORMAT=json: satu objek JSON per baris dengan field terstruktur dan span request
+        let json_logs = config.server.log_format == LogFormat::Json;
+        tracing_subscriber::registry()
+            .with(log_filter)
+            .with((!json_logs).then(tracing_subscriber::fmt::layer))
+            .with(json_logs.then(|| tracing_subscriber::fmt::layer().json().flatten_event(true)))
+            .with(otel_layer)
+            .init();
+
+        if let Some(e) = otlp_error {
+            warn!("[TELEMETRY] OTLP export disabled: {}", e);
+        } else if let Some(endpoint) = &config.telemetry.otlp_endpoint {
+            info!("[TELEMETRY] Exporting traces to {}", Self::traces_url(endpoint));
+        }
+
+        Self { log_handle, tracer_provider }
+    }
+
+    /// Kirim span yang masih di buffer batch exporter
+    pub fn shutdown(self) {
+        if let Some(provider) = self.tracer_provider {
+            if let Err(e) = provider.shutdown() {
+                warn!("[TELEMETRY] Failed to flush traces: {}", e);
+            }
+        }
+    }
+
+    fn otlp_provider(config: &TelemetryConfig) -> Result<Option<SdkTracerProvider>, String> {
+        let Some(endpoint) = &config.otlp_endpoint else {
+            return Ok(None);
+        };
+
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(Self::traces_url(endpoint))
+            .build()
+            .map_err(|e| e.to_string())?;
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(Resource::builder().with_service_name(config.service_name.clone()).build())
+            .build();
+        Ok(Some(provider))
+    }
+
+    /// Endpoint di config adalah base collector (`http://localhost:4318`), path traces ditambahkan di sini
+    fn traces_url(endpoint: &str) -> String {
+        let endpoint = endpoint.trim_end_matches('/');
+        if endpoint.ends_with(OTLP_TRACES_PATH) {
+            endpoint.to_string()
+        } else {
+            format!("{}{}", endpoint, OTLP_TRACES_PATH)
+        }
+    }
+}